@@ -0,0 +1,200 @@
+use std::{
+    sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use axum::{extract::Query, Extension};
+use byte_unit::Byte;
+use http_negotiator::{ContentTypeNegotiation, Negotiation};
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+
+use crate::{
+    admin::Administrator,
+    error::{administration as AdministrationError, Error},
+    include_query,
+    info::{FileMetadata, FileRow},
+    response::{ApiHeader, ApiResponse, ResponseType, SingleLine},
+    upload::{Determiner, DomainUri},
+};
+
+const DEFAULT_LIMIT: u32 = 100;
+
+// Sizes use byte units (10MB) and ages humantime durations (3d 12h).
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "kebab-case")]
+pub struct SearchQuery {
+    origin: Option<String>,
+    name: Option<String>,
+    min_size: Option<String>,
+    max_size: Option<String>,
+    older_than: Option<String>,
+    newer_than: Option<String>,
+    limit: Option<u32>,
+    offset: Option<u32>,
+    // Lists files waiting in the trash instead of available ones.
+    #[serde(default)]
+    trashed: bool,
+}
+
+#[derive(PartialEq, Eq, Debug)]
+struct Search {
+    origin: Option<String>,
+    // LIKE pattern with wildcards escaped.
+    name: Option<String>,
+    min_size: Option<i64>,
+    max_size: Option<i64>,
+    uploaded_before: Option<i64>,
+    uploaded_after: Option<i64>,
+    limit: u32,
+    offset: u32,
+    trashed: bool,
+}
+
+impl Search {
+    fn new(query: SearchQuery, now: u64) -> Option<Self> {
+        let size = |size: Option<String>| match size {
+            Some(size) => size
+                .parse::<Byte>()
+                .ok()
+                .map(|size| Some(size.get_bytes() as i64)),
+            None => Some(None),
+        };
+        let uploaded = |age: Option<String>| match age {
+            Some(age) => age
+                .parse::<humantime::Duration>()
+                .ok()
+                .map(|age| Some(now.saturating_sub(age.as_secs()) as i64)),
+            None => Some(None),
+        };
+        Some(Self {
+            origin: query.origin.filter(|origin| !origin.is_empty()),
+            name: query.name.filter(|name| !name.is_empty()).map(|name| {
+                format!(
+                    "%{}%",
+                    name.replace('\\', "\\\\")
+                        .replace('%', "\\%")
+                        .replace('_', "\\_")
+                )
+            }),
+            min_size: size(query.min_size)?,
+            max_size: size(query.max_size)?,
+            uploaded_before: uploaded(query.older_than)?,
+            uploaded_after: uploaded(query.newer_than)?,
+            limit: query.limit.unwrap_or(DEFAULT_LIMIT),
+            offset: query.offset.unwrap_or(0),
+            trashed: query.trashed,
+        })
+    }
+}
+
+#[derive(Serialize)]
+pub struct Files {
+    files: Vec<FileMetadata>,
+}
+
+impl ApiHeader for Files {}
+
+impl SingleLine for Files {
+    fn single_lined(&self) -> String {
+        self.files.iter().map(|file| file.single_lined()).join("\n")
+    }
+}
+
+// Lists every uploaded file matching the filters, most recent first.
+pub async fn handler(
+    Extension(pool): Extension<SqlitePool>,
+    response_type: Negotiation<ContentTypeNegotiation, ResponseType>,
+    _: Administrator,
+    Query(query): Query<SearchQuery>,
+    DomainUri(domain_uri): DomainUri,
+    Extension(determiner): Extension<Arc<Determiner>>,
+) -> Result<ApiResponse<Files>, ApiResponse<Error>> {
+    process_search(pool, query, domain_uri, &determiner)
+        .await
+        .map(|files| ApiResponse(*response_type, files))
+        .map_err(|err| ApiResponse(*response_type, err))
+}
+
+async fn process_search(
+    pool: SqlitePool,
+    query: SearchQuery,
+    domain_uri: String,
+    determiner: &Determiner,
+) -> Result<Files, Error> {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_err(|_| AdministrationError::TimeCalculation)?
+        .as_secs();
+    let search = Search::new(query, now).ok_or(AdministrationError::InvalidSearch)?;
+    let rows = sqlx::query_as::<_, FileRow>(include_query!("get_files_search"))
+        .bind(search.origin)
+        .bind(search.name)
+        .bind(search.min_size)
+        .bind(search.max_size)
+        .bind(search.uploaded_before)
+        .bind(search.uploaded_after)
+        .bind(search.limit)
+        .bind(search.offset)
+        .bind(search.trashed)
+        .fetch_all(&pool)
+        .await
+        .map_err(|_| AdministrationError::Database)?;
+    Ok(Files {
+        files: rows
+            .into_iter()
+            .map(|row| FileMetadata::new(row, &domain_uri, Some(determiner)))
+            .collect::<Result<_, _>>()?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Search, SearchQuery, DEFAULT_LIMIT};
+
+    #[test]
+    fn search() {
+        let search = Search::new(
+            SearchQuery {
+                name: Some("100%_done".to_owned()),
+                min_size: Some("1KB".to_owned()),
+                older_than: Some("1h".to_owned()),
+                ..Default::default()
+            },
+            10_000,
+        )
+        .unwrap();
+        assert_eq!(
+            search,
+            Search {
+                origin: None,
+                name: Some("%100\\%\\_done%".to_owned()),
+                min_size: Some(1000),
+                max_size: None,
+                uploaded_before: Some(6400),
+                uploaded_after: None,
+                limit: DEFAULT_LIMIT,
+                offset: 0,
+                trashed: false,
+            }
+        );
+
+        assert!(Search::new(
+            SearchQuery {
+                max_size: Some("large".to_owned()),
+                ..Default::default()
+            },
+            0
+        )
+        .is_none());
+        assert!(Search::new(
+            SearchQuery {
+                newer_than: Some("yesterday".to_owned()),
+                ..Default::default()
+            },
+            0
+        )
+        .is_none());
+    }
+}
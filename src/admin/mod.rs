@@ -0,0 +1,66 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use axum::{
+    extract::{FromRequest, RequestParts},
+    routing::get,
+    Extension, Router,
+};
+use hyper::Body;
+use sqlx::SqlitePool;
+
+use crate::{
+    auth::Authenticator,
+    error::{administration as AdministrationError, Error},
+    storage::Dir,
+    update::AdminAccess,
+    upload::Determiner,
+};
+
+mod files;
+mod origins;
+mod sessions;
+mod stats;
+
+// Limits configured on the command line, reported next to the current usage.
+pub struct Quotas {
+    pub origin_size_sum: u64,
+    pub origin_file_count: usize,
+    pub global_size_sum: u64,
+}
+
+// Only accepts server administrators. Moderation actions (revoking or extending a file) reuse the update endpoints.
+pub struct Administrator;
+
+#[async_trait]
+impl FromRequest<Body> for Administrator {
+    type Rejection = Error;
+
+    async fn from_request(req: &mut RequestParts<Body>) -> Result<Self, Self::Rejection> {
+        match AdminAccess::from_request(req).await? {
+            AdminAccess::Administrator(_) => Ok(Self),
+            AdminAccess::Token(_) | AdminAccess::Owner(_) => {
+                Err(AdministrationError::AdministratorRequired)
+            }
+        }
+    }
+}
+
+pub fn router(
+    pool: SqlitePool,
+    dir: Dir,
+    authenticator: Arc<Authenticator>,
+    determiner: Arc<Determiner>,
+    quotas: Arc<Quotas>,
+) -> Router {
+    Router::new()
+        .route("/admin/files", get(files::handler))
+        .route("/admin/origins", get(origins::handler))
+        .route("/admin/sessions", get(sessions::handler))
+        .route("/admin/stats", get(stats::handler))
+        .route_layer(Extension(pool))
+        .route_layer(Extension(dir))
+        .route_layer(Extension(authenticator))
+        .route_layer(Extension(determiner))
+        .route_layer(Extension(quotas))
+}
@@ -0,0 +1,84 @@
+use std::sync::Arc;
+
+use axum::Extension;
+use http_negotiator::{ContentTypeNegotiation, Negotiation};
+use itertools::Itertools;
+use serde::Serialize;
+use sqlx::SqlitePool;
+
+use crate::{
+    admin::{Administrator, Quotas},
+    error::{administration as AdministrationError, Error},
+    include_query,
+    response::{ApiHeader, ApiResponse, ResponseType, SingleLine},
+    upload::Size,
+};
+
+#[derive(Serialize)]
+pub struct Origins {
+    limit: OriginLimit,
+    origins: Vec<OriginUsage>,
+}
+
+#[derive(Serialize)]
+struct OriginLimit {
+    size: Size,
+    files: usize,
+}
+
+#[derive(Serialize)]
+struct OriginUsage {
+    origin: String,
+    size: Size,
+    files: usize,
+}
+
+impl ApiHeader for Origins {}
+
+impl SingleLine for Origins {
+    fn single_lined(&self) -> String {
+        self.origins
+            .iter()
+            .map(|usage| {
+                format!(
+                    "{} {}/{} {}/{}",
+                    usage.origin,
+                    usage.size.bytes,
+                    self.limit.size.bytes,
+                    usage.files,
+                    self.limit.files
+                )
+            })
+            .join("\n")
+    }
+}
+
+// Usage of every uploader compared to the per origin limits, pending uploads included.
+pub async fn handler(
+    Extension(pool): Extension<SqlitePool>,
+    response_type: Negotiation<ContentTypeNegotiation, ResponseType>,
+    _: Administrator,
+    Extension(quotas): Extension<Arc<Quotas>>,
+) -> Result<ApiResponse<Origins>, ApiResponse<Error>> {
+    let origins = sqlx::query_as::<_, (String, i64, i64)>(include_query!("get_origins_usage"))
+        .fetch_all(&pool)
+        .await
+        .map_err(|_| ApiResponse(*response_type, AdministrationError::Database))?;
+    Ok(ApiResponse(
+        *response_type,
+        Origins {
+            limit: OriginLimit {
+                size: Size::from(quotas.origin_size_sum),
+                files: quotas.origin_file_count,
+            },
+            origins: origins
+                .into_iter()
+                .map(|(origin, size, files)| OriginUsage {
+                    origin,
+                    size: Size::from(size as u64),
+                    files: files as usize,
+                })
+                .collect(),
+        },
+    ))
+}
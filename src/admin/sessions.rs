@@ -0,0 +1,53 @@
+use std::sync::Arc;
+
+use axum::Extension;
+use http_negotiator::{ContentTypeNegotiation, Negotiation};
+use itertools::Itertools;
+use serde::Serialize;
+
+use crate::{
+    admin::Administrator,
+    auth::{Authenticator, Role},
+    response::{ApiHeader, ApiResponse, ResponseType, SingleLine},
+};
+
+#[derive(Serialize)]
+pub struct Sessions {
+    users: Vec<UserSessions>,
+}
+
+#[derive(Serialize)]
+struct UserSessions {
+    username: String,
+    sessions: usize,
+    administrator: bool,
+}
+
+impl ApiHeader for Sessions {}
+
+impl SingleLine for Sessions {
+    fn single_lined(&self) -> String {
+        self.users
+            .iter()
+            .map(|user| format!("{} {}", user.username, user.sessions))
+            .join("\n")
+    }
+}
+
+// Sessions only live in memory, so they are reset on restart.
+pub async fn handler(
+    response_type: Negotiation<ContentTypeNegotiation, ResponseType>,
+    _: Administrator,
+    Extension(authenticator): Extension<Arc<Authenticator>>,
+) -> ApiResponse<Sessions> {
+    let mut users = Vec::new();
+    for (username, sessions) in authenticator.sessions().await {
+        users.push(UserSessions {
+            administrator: authenticator.role(&username).await == Role::Administrator,
+            username,
+            sessions,
+        });
+    }
+    users.sort_by(|a, b| a.username.cmp(&b.username));
+    ApiResponse(*response_type, Sessions { users })
+}
@@ -0,0 +1,85 @@
+use std::sync::Arc;
+
+use axum::Extension;
+use http_negotiator::{ContentTypeNegotiation, Negotiation};
+use serde::Serialize;
+use sqlx::{FromRow, SqlitePool};
+
+use crate::{
+    admin::{Administrator, Quotas},
+    error::{administration as AdministrationError, Error},
+    include_query,
+    response::{ApiHeader, ApiResponse, ResponseType, SingleLine},
+    storage::Dir,
+    upload::Size,
+};
+
+#[derive(FromRow)]
+struct StatsRow {
+    files: i64,
+    pending: i64,
+    trashed: i64,
+    collections: i64,
+    size: i64,
+}
+
+#[derive(Serialize)]
+pub struct Stats {
+    files: usize,
+    pending: usize,
+    trashed: usize,
+    collections: usize,
+    size: Size,
+    limit: Size,
+    available: Size,
+}
+
+impl ApiHeader for Stats {}
+
+impl SingleLine for Stats {
+    fn single_lined(&self) -> String {
+        format!(
+            "{} {} {} {} {}/{} {}",
+            self.files,
+            self.pending,
+            self.trashed,
+            self.collections,
+            self.size.bytes,
+            self.limit.bytes,
+            self.available.bytes
+        )
+    }
+}
+
+pub async fn handler(
+    Extension(pool): Extension<SqlitePool>,
+    response_type: Negotiation<ContentTypeNegotiation, ResponseType>,
+    _: Administrator,
+    Extension(quotas): Extension<Arc<Quotas>>,
+    Extension(dir): Extension<Dir>,
+) -> Result<ApiResponse<Stats>, ApiResponse<Error>> {
+    process_stats(pool, &quotas, dir)
+        .await
+        .map(|stats| ApiResponse(*response_type, stats))
+        .map_err(|err| ApiResponse(*response_type, err))
+}
+
+async fn process_stats(pool: SqlitePool, quotas: &Quotas, dir: Dir) -> Result<Stats, Error> {
+    let row = sqlx::query_as::<_, StatsRow>(include_query!("get_stats"))
+        .fetch_one(&pool)
+        .await
+        .map_err(|_| AdministrationError::Database)?;
+    let available = dir.available_space().await.map_err(|err| {
+        log::error!("Cannot determine uploads directory free space: {}", err);
+        AdministrationError::StorageAccess
+    })?;
+    Ok(Stats {
+        files: row.files as usize,
+        pending: row.pending as usize,
+        trashed: row.trashed as usize,
+        collections: row.collections as usize,
+        size: Size::from(row.size as u64),
+        limit: Size::from(quotas.global_size_sum),
+        available: Size::from(available),
+    })
+}
@@ -43,4 +43,12 @@ pub fn router() -> Router {
         .route("/login/index.html", get(handler))
         .route("/login/style.css", get(handler))
         .route("/login/app.js", get(handler))
+        .route("/uploads/", get(handler))
+        .route("/uploads/index.html", get(handler))
+        .route("/uploads/style.css", get(handler))
+        .route("/uploads/app.js", get(handler))
+        .route("/admin/", get(handler))
+        .route("/admin/index.html", get(handler))
+        .route("/admin/style.css", get(handler))
+        .route("/admin/app.js", get(handler))
 }
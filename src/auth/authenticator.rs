@@ -1,11 +1,11 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 use axum::headers::{authorization::Basic, Authorization, Cookie};
 use tokio::sync::RwLock;
 use uuid::Uuid;
 
 use crate::{
-    auth::{Credential, Features, LdapAuthenticator},
+    auth::{Credential, Features, LdapAuthenticator, Role},
     error::{auth as AuthError, Error},
 };
 
@@ -27,6 +27,9 @@ pub struct Authenticator {
     static_credentials: HashMap<String, String>,
     ldap: Option<LdapAuthenticator>,
     sessions: RwLock<HashMap<String, String>>,
+    admins: HashSet<String>,
+    // LDAP group membership is only known after verifying credentials.
+    ldap_admins: RwLock<HashSet<String>>,
 }
 
 impl Authenticator {
@@ -34,6 +37,7 @@ impl Authenticator {
         protected: Features,
         credentials: Vec<Credential>,
         ldap: Option<LdapAuthenticator>,
+        admins: Vec<String>,
     ) -> Self {
         Self {
             protected,
@@ -43,19 +47,50 @@ impl Authenticator {
                 .collect(),
             ldap,
             sessions: Default::default(),
+            admins: admins.into_iter().collect(),
+            ldap_admins: Default::default(),
         }
     }
 
+    pub async fn role(&self, username: &str) -> Role {
+        if self.admins.contains(username) || self.ldap_admins.read().await.contains(username) {
+            Role::Administrator
+        } else {
+            Role::User
+        }
+    }
+
+    // Number of active sessions per username.
+    pub async fn sessions(&self) -> HashMap<String, usize> {
+        let mut sessions = HashMap::new();
+        for username in self.sessions.read().await.values() {
+            *sessions.entry(username.clone()).or_insert(0) += 1;
+        }
+        sessions
+    }
+
     pub async fn allows(
         &self,
         authorization: Option<Authorization<Basic>>,
         cookie: Option<Cookie>,
         feature: Features,
     ) -> AuthStatus {
-        if !self.protected.contains(feature) {
+        if !self.protects(feature) {
             return AuthStatus::NotNeeded;
         }
+        self.identify(authorization, cookie).await
+    }
 
+    pub fn protects(&self, feature: Features) -> bool {
+        self.protected.contains(feature)
+    }
+
+    // Verifies credentials even if no feature is protected, to act on behalf of an uploader.
+    pub async fn identify(
+        &self,
+        authorization: Option<Authorization<Basic>>,
+        cookie: Option<Cookie>,
+    ) -> AuthStatus {
         match self.verify_authorization_header(authorization).await {
             AuthProcess::Valid(username) => return AuthStatus::Valid(username),
             AuthProcess::Continue => (),
@@ -114,8 +149,16 @@ impl Authenticator {
 
         if let Some(ldap) = &self.ldap {
             return match ldap.is_authorized(username, password).await {
-                Ok(true) => AuthProcess::Valid(username.to_owned()),
-                Ok(false) => AuthProcess::Stop,
+                Ok(Some(role)) => {
+                    let mut ldap_admins = self.ldap_admins.write().await;
+                    if role == Role::Administrator {
+                        ldap_admins.insert(username.to_owned());
+                    } else {
+                        ldap_admins.remove(username);
+                    }
+                    AuthProcess::Valid(username.to_owned())
+                }
+                Ok(None) => AuthProcess::Stop,
                 Err(err) => {
                     log::error!("Cannot authenticate user using LDAP: {:?}", err);
                     AuthProcess::Stop
@@ -1,26 +1,59 @@
 use ldap3::{ldap_escape, Ldap, LdapConnAsync, LdapError, Scope, SearchEntry};
 
+use crate::auth::Role;
+
 pub struct LdapAuthenticator {
     address: String,
     process: LdapAuthProcess,
+    admin_group: Option<String>,
 }
 
 impl LdapAuthenticator {
-    pub fn new(address: String, process: LdapAuthProcess) -> Self {
-        Self { address, process }
+    pub fn new(address: String, process: LdapAuthProcess, admin_group: Option<String>) -> Self {
+        Self {
+            address,
+            process,
+            admin_group,
+        }
     }
 
-    pub async fn is_authorized(&self, username: &str, password: &str) -> Result<bool, LdapError> {
+    pub async fn is_authorized(
+        &self,
+        username: &str,
+        password: &str,
+    ) -> Result<Option<Role>, LdapError> {
         let (conn, mut ldap) = LdapConnAsync::new(&self.address).await?;
         ldap3::drive!(conn);
 
         let bind_dn = match self.process.resolve_dn(&mut ldap, username).await? {
-            None => return Ok(false),
+            None => return Ok(None),
             Some(bind_dn) => bind_dn,
         };
         let res = ldap.simple_bind(&bind_dn, password).await?;
+        if res.success().is_err() {
+            return Ok(None);
+        }
 
-        Ok(res.success().is_ok())
+        // Membership is checked with the user's own bind, failing to compare only denies the role.
+        let group = match &self.admin_group {
+            Some(group) => group,
+            None => return Ok(Some(Role::User)),
+        };
+        match ldap
+            .compare(group, "member", &bind_dn)
+            .await
+            .and_then(|res| res.equal())
+        {
+            Ok(true) => Ok(Some(Role::Administrator)),
+            Ok(false) => Ok(Some(Role::User)),
+            Err(err) => {
+                log::warn!(
+                    "Cannot check LDAP administrators group membership: {:?}",
+                    err
+                );
+                Ok(Some(Role::User))
+            }
+        }
     }
 }
 
@@ -6,6 +6,7 @@ pub use credential::Credential;
 pub use features::Features;
 pub use ldap::{LdapAuthProcess, LdapAuthenticator};
 pub use origin::Origin;
+pub use role::Role;
 
 mod authenticator;
 mod credential;
@@ -14,6 +15,7 @@ mod ldap;
 mod login;
 mod origin;
 mod protection;
+mod role;
 
 pub fn router(authenticator: Arc<Authenticator>) -> Router {
     Router::new()
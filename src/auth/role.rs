@@ -0,0 +1,5 @@
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Role {
+    User,
+    Administrator,
+}
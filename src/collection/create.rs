@@ -0,0 +1,132 @@
+use std::{convert::TryFrom, sync::Arc};
+
+use axum::{Extension, Json};
+use http_negotiator::{ContentTypeNegotiation, Negotiation};
+use sanitize_filename::sanitize;
+use serde::Deserialize;
+use sqlx::{Connection, SqlitePool};
+use uuid::Uuid;
+
+use crate::{
+    alias,
+    alias::Alias,
+    error::{collection as CollectionError, Error},
+    include_query,
+    response::{ApiResponse, ResponseType},
+    storage::Scheduler,
+    update::{check_admin_token, new_admin_token},
+    upload::{Determiner, DomainUri, Expiration, ExpirationDuration, UploadInfo},
+};
+
+#[derive(Deserialize)]
+pub struct CollectionRequest {
+    name: Option<String>,
+    files: Vec<Member>,
+}
+
+#[derive(Deserialize)]
+struct Member {
+    alias: Alias,
+    admin: String,
+}
+
+pub async fn handler(
+    Extension(pool): Extension<SqlitePool>,
+    response_type: Negotiation<ContentTypeNegotiation, ResponseType>,
+    Extension(determiner): Extension<Arc<Determiner>>,
+    Extension(scheduler): Extension<Arc<Scheduler>>,
+    DomainUri(domain_uri): DomainUri,
+    Json(request): Json<CollectionRequest>,
+) -> Result<ApiResponse<UploadInfo>, ApiResponse<Error>> {
+    Ok(ApiResponse(
+        *response_type,
+        process_create(pool, determiner, &scheduler, domain_uri, request)
+            .await
+            .map_err(|err| ApiResponse(*response_type, err))?,
+    ))
+}
+
+async fn process_create(
+    pool: SqlitePool,
+    determiner: Arc<Determiner>,
+    scheduler: &Scheduler,
+    domain_uri: String,
+    request: CollectionRequest,
+) -> Result<UploadInfo, Error> {
+    let mut conn = pool
+        .acquire()
+        .await
+        .map_err(|_| CollectionError::Database)?;
+
+    // Only files administrated by the caller can be grouped, in the requested order.
+    let mut origin = None;
+    let mut size = 0;
+    let mut members = Vec::with_capacity(request.files.len());
+    for member in request.files {
+        let (id, file_origin, file_size, admin) =
+            sqlx::query_as::<_, (String, String, i64, String)>(include_query!("get_file_member"))
+                .bind(member.alias.inner())
+                .bind(member.alias.inner())
+                .fetch_optional(&mut conn)
+                .await
+                .map_err(|_| CollectionError::Database)?
+                .ok_or(CollectionError::FileNotFound)?;
+        check_admin_token(&admin, &member.admin)?;
+        if members.contains(&id) {
+            continue;
+        }
+        origin.get_or_insert(file_origin);
+        size += file_size as u64;
+        members.push(id);
+    }
+    let origin = origin.ok_or(CollectionError::EmptyCollection)?;
+
+    let (short, long) = alias::random_unused_aliases(&mut conn)
+        .await
+        .ok_or(CollectionError::AliasGeneration)?;
+
+    let (default_duration, allowed_duration) = determiner
+        .determine(size)
+        .ok_or(CollectionError::TooLarge)?;
+    let default_expiration = Expiration::try_from(default_duration)?;
+
+    let id = Uuid::new_v4().as_hyphenated().to_string();
+    let (admin, admin_hash) = new_admin_token();
+    let name = request.name.map(sanitize).filter(|name| !name.is_empty());
+
+    let mut tx = conn.begin().await.map_err(|_| CollectionError::Database)?;
+    sqlx::query(include_query!("insert_collection"))
+        .bind(&id)
+        .bind(admin_hash)
+        .bind(&origin)
+        .bind(default_expiration.timestamp() as i64)
+        .bind(&name)
+        .bind(&short)
+        .bind(&long)
+        .execute(&mut tx)
+        .await
+        .map_err(|_| CollectionError::Database)?;
+    for (position, file_id) in members.iter().enumerate() {
+        sqlx::query(include_query!("insert_collection_file"))
+            .bind(&id)
+            .bind(file_id)
+            .bind(position as i64)
+            .execute(&mut tx)
+            .await
+            .map_err(|_| CollectionError::Database)?;
+    }
+    tx.commit().await.map_err(|_| CollectionError::Database)?;
+    scheduler.schedule(default_expiration.timestamp());
+
+    Ok(UploadInfo::new(
+        Some(admin),
+        name.unwrap_or_else(|| long.clone()),
+        size,
+        (short, long),
+        domain_uri,
+        (
+            default_expiration,
+            allowed_duration.map(ExpirationDuration::from),
+        ),
+    ))
+}
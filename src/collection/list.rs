@@ -0,0 +1,74 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use axum::{
+    response::{Html, IntoResponse, Response},
+    Extension,
+};
+use sqlx::{FromRow, SqlitePool};
+
+use crate::{
+    alias::Alias,
+    collection::Collection,
+    error::{collection as CollectionError, Error},
+    include_query,
+    misc::{escape_html, format_duration},
+    upload::Size,
+};
+
+#[derive(FromRow)]
+struct Member {
+    name: String,
+    size: i64,
+    short_alias: String,
+}
+
+pub async fn handler(
+    Extension(pool): Extension<SqlitePool>,
+    alias: Alias,
+) -> Result<Response, Error> {
+    let mut conn = pool
+        .acquire()
+        .await
+        .map_err(|_| CollectionError::Database)?;
+    let collection = Collection::find(&alias, &mut conn)
+        .await
+        .map_err(|_| CollectionError::Database)?
+        .ok_or(CollectionError::CollectionNotFound)?;
+    let members = sqlx::query_as::<_, Member>(include_query!("get_collection_files"))
+        .bind(&collection.id)
+        .fetch_all(&mut conn)
+        .await
+        .map_err(|_| CollectionError::Database)?;
+
+    let rows = members
+        .iter()
+        .map(|member| {
+            format!(
+                r#"        <tr><td><a href="/{}">{}</a></td><td class="size">{}</td></tr>"#,
+                member.short_alias,
+                escape_html(&member.name),
+                Size::from(member.size as u64).readable,
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    let remaining = (collection.expiration as u64).saturating_sub(
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default(),
+    );
+    let description = format!(
+        "{} file(s), {} in total, expires in {}",
+        members.len(),
+        Size::from(members.iter().map(|m| m.size as u64).sum::<u64>()).readable,
+        format_duration(Duration::from_secs(remaining)),
+    );
+
+    let page = include_str!("list.html")
+        .replace("$TITLE", &escape_html(&collection.name))
+        .replace("$DESCRIPTION", &description)
+        .replacen("$ROWS", &rows, 1)
+        .replacen("$ALIAS", &collection.short_alias, 1);
+    Ok(Html(page).into_response())
+}
@@ -0,0 +1,97 @@
+use std::sync::Arc;
+
+use axum::{
+    routing::{get, patch, post},
+    Extension, Router,
+};
+use sqlx::{FromRow, SqliteConnection, SqlitePool};
+
+use crate::{alias::Alias, include_query, storage::Scheduler, upload::Determiner};
+
+mod create;
+mod list;
+mod update;
+
+#[derive(FromRow)]
+pub struct Collection {
+    pub id: String,
+    pub name: String,
+    pub short_alias: String,
+    pub expiration: i64,
+    pub downloads: Option<u16>,
+}
+
+impl Collection {
+    pub async fn find(
+        alias: &Alias,
+        conn: &mut SqliteConnection,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as::<_, Self>(include_query!("get_collection"))
+            .bind(alias.inner())
+            .bind(alias.inner())
+            .fetch_optional(conn)
+            .await
+    }
+}
+
+// Unlike its members' counters, the collection counter is decremented once per archive download.
+pub async fn collection_downloaded(
+    pool: &SqlitePool,
+    collection: &Collection,
+) -> Result<(), String> {
+    match collection.downloads {
+        None => (),
+        Some(0) => {
+            return Err(format!(
+                "Found a zero downloads counter collection: {}",
+                collection.id
+            ))
+        }
+        Some(1) => {
+            sqlx::query(include_query!("delete_collection"))
+                .bind(&collection.id)
+                .execute(pool)
+                .await
+                .map_err(|err| {
+                    format!(
+                        "Failed to delete decremented to zero collection {}: {:?}",
+                        collection.id, err
+                    )
+                })?;
+        }
+        Some(count) => {
+            sqlx::query(include_query!("update_collection_downloads"))
+                .bind(count - 1)
+                .bind(&collection.id)
+                .execute(pool)
+                .await
+                .map_err(|err| {
+                    format!(
+                        "Failed to decremented collection {}: {:?}",
+                        collection.id, err
+                    )
+                })?;
+        }
+    }
+    Ok(())
+}
+
+pub fn router(pool: SqlitePool, determiner: Arc<Determiner>, scheduler: Arc<Scheduler>) -> Router {
+    Router::new()
+        .route("/collection", post(create::handler))
+        .route(
+            "/collection/:alias",
+            get(list::handler).delete(update::revoke_handler),
+        )
+        .route(
+            "/collection/:alias/downloads/:count",
+            patch(update::downloads_handler),
+        )
+        .route(
+            "/collection/:alias/expiration/:duration",
+            patch(update::expiration_handler),
+        )
+        .route_layer(Extension(pool))
+        .route_layer(Extension(determiner))
+        .route_layer(Extension(scheduler))
+}
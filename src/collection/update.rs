@@ -0,0 +1,150 @@
+use std::{convert::TryFrom, sync::Arc, time::Duration};
+
+use axum::{extract::Path, Extension};
+use http_negotiator::{ContentTypeNegotiation, Negotiation};
+use sqlx::{pool::PoolConnection, Sqlite, SqlitePool};
+
+use crate::{
+    alias::Alias,
+    error::{collection as CollectionError, Error},
+    include_query,
+    response::{ApiResponse, ResponseType},
+    storage::Scheduler,
+    update::{check_admin_token, AdminToken, DurationRequest},
+    upload::{Determiner, Expiration},
+};
+
+async fn authorize(
+    pool: SqlitePool,
+    alias: &Alias,
+    admin_token: &str,
+) -> Result<(String, PoolConnection<Sqlite>), Error> {
+    let mut conn = pool
+        .acquire()
+        .await
+        .map_err(|_| CollectionError::Database)?;
+
+    let (id, admin) = sqlx::query_as::<_, (String, String)>(include_query!("get_collection_admin"))
+        .bind(alias.inner())
+        .bind(alias.inner())
+        .fetch_optional(&mut conn)
+        .await
+        .map_err(|_| CollectionError::Database)?
+        .ok_or(CollectionError::CollectionNotFound)?;
+
+    check_admin_token(&admin, admin_token)?;
+    Ok((id, conn))
+}
+
+// Revoking a collection leaves its member files untouched.
+pub async fn revoke_handler(
+    Extension(pool): Extension<SqlitePool>,
+    response_type: Negotiation<ContentTypeNegotiation, ResponseType>,
+    AdminToken(admin_token): AdminToken,
+    alias: Alias,
+) -> Result<ApiResponse<()>, ApiResponse<Error>> {
+    process_revoke(pool, alias, admin_token)
+        .await
+        .map_err(|err| ApiResponse(*response_type, err))?;
+    Ok(ApiResponse(*response_type, ()))
+}
+
+async fn process_revoke(pool: SqlitePool, alias: Alias, admin_token: String) -> Result<(), Error> {
+    let (id, mut conn) = authorize(pool, &alias, &admin_token).await?;
+    sqlx::query(include_query!("delete_collection"))
+        .bind(&id)
+        .execute(&mut conn)
+        .await
+        .map_err(|_| CollectionError::Database)?;
+    Ok(())
+}
+
+pub async fn downloads_handler(
+    Extension(pool): Extension<SqlitePool>,
+    response_type: Negotiation<ContentTypeNegotiation, ResponseType>,
+    AdminToken(admin_token): AdminToken,
+    alias: Alias,
+    Path((_, count)): Path<(String, u16)>,
+) -> Result<ApiResponse<()>, ApiResponse<Error>> {
+    process_downloads(pool, alias, admin_token, count)
+        .await
+        .map_err(|err| ApiResponse(*response_type, err))?;
+    Ok(ApiResponse(*response_type, ()))
+}
+
+async fn process_downloads(
+    pool: SqlitePool,
+    alias: Alias,
+    admin_token: String,
+    count: u16,
+) -> Result<(), Error> {
+    let (id, mut conn) = authorize(pool, &alias, &admin_token).await?;
+    let count = if count >= 1 { Some(count) } else { None };
+
+    sqlx::query(include_query!("update_collection_downloads"))
+        .bind(count)
+        .bind(&id)
+        .execute(&mut conn)
+        .await
+        .map_err(|_| CollectionError::UnexpectedFileModification)?;
+    Ok(())
+}
+
+pub async fn expiration_handler(
+    Extension(pool): Extension<SqlitePool>,
+    response_type: Negotiation<ContentTypeNegotiation, ResponseType>,
+    Extension(determiner): Extension<Arc<Determiner>>,
+    Extension(scheduler): Extension<Arc<Scheduler>>,
+    AdminToken(admin_token): AdminToken,
+    alias: Alias,
+    Path((_, duration)): Path<(String, DurationRequest)>,
+) -> Result<ApiResponse<Expiration>, ApiResponse<Error>> {
+    Ok(ApiResponse(
+        *response_type,
+        process_extend(pool, determiner, &scheduler, alias, duration, admin_token)
+            .await
+            .map_err(|err| ApiResponse(*response_type, err))?,
+    ))
+}
+
+// Allowed durations are determined using the total size of the collection.
+async fn process_extend(
+    pool: SqlitePool,
+    determiner: Arc<Determiner>,
+    scheduler: &Scheduler,
+    alias: Alias,
+    duration: DurationRequest,
+    admin_token: String,
+) -> Result<Expiration, Error> {
+    let (id, mut conn) = authorize(pool, &alias, &admin_token).await?;
+    let (size,) = sqlx::query_as::<_, (i64,)>(include_query!("get_collection_size"))
+        .bind(&id)
+        .fetch_one(&mut conn)
+        .await
+        .map_err(|_| CollectionError::Database)?;
+
+    let (default, allowed) = determiner
+        .determine(size as u64)
+        .ok_or(CollectionError::TooLarge)?;
+    let expiration = Expiration::try_from(match duration {
+        DurationRequest::Initial => default,
+        DurationRequest::Maximum => allowed.ok_or(CollectionError::ExpirationTooHigh)?,
+        DurationRequest::Custom(secs) => {
+            let dur = Duration::from_secs(secs);
+            if dur > allowed.unwrap_or(default) {
+                return Err(CollectionError::ExpirationTooHigh);
+            }
+            dur
+        }
+    })?;
+
+    sqlx::query(include_query!("extend_collection"))
+        .bind(expiration.timestamp() as i64)
+        .bind(&id)
+        .execute(&mut conn)
+        .await
+        .map_err(|_| CollectionError::Database)?;
+    scheduler.schedule(expiration.timestamp());
+
+    Ok(expiration)
+}
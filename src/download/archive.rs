@@ -1,89 +1,210 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, io::Error as IoError, sync::Arc};
 
+use async_compression::tokio::bufread::GzipEncoder;
+use async_trait::async_trait;
 use axum::{
     body::StreamBody,
     response::{IntoResponse, Response},
 };
 use hyper::{
     header::{HeaderValue, CONTENT_DISPOSITION, CONTENT_LENGTH, CONTENT_TYPE},
-    StatusCode,
+    HeaderMap, StatusCode,
 };
+use serde::Deserialize;
 use sqlx::SqlitePool;
-use tokio::io::duplex;
+use tokio::io::{duplex, AsyncRead, BufReader};
 use tokio_util::io::ReaderStream;
-use zipit::{archive_size, Archive, FileDateTime};
 
-use crate::{download::FileInfo, error::Error, storage::Dir};
+use crate::{
+    download::{
+        tar::{tar_size, Tar},
+        zip::{zip_size, Zip},
+        FileInfo,
+    },
+    error::Error,
+    storage::{Cleaner, Dir},
+};
+
+#[derive(Deserialize, Copy, Clone, PartialEq, Eq, Debug)]
+pub enum ArchiveFormat {
+    #[serde(rename = "zip")]
+    Zip,
+    #[serde(rename = "tar")]
+    Tar,
+    #[serde(rename = "tar.gz", alias = "tgz")]
+    TarGz,
+}
+
+impl ArchiveFormat {
+    pub fn from_accept(accept: &str) -> Option<Self> {
+        accept.split(',').find_map(|media| {
+            match media.split(';').next().unwrap_or_default().trim() {
+                "application/zip" => Some(Self::Zip),
+                "application/x-tar" => Some(Self::Tar),
+                "application/gzip" | "application/x-gzip" | "application/x-gtar" => {
+                    Some(Self::TarGz)
+                }
+                _ => None,
+            }
+        })
+    }
+}
 
 pub(super) async fn handler(
     pool: SqlitePool,
     mut files_info: Vec<FileInfo>,
     dir: Dir,
+    cleaner: Arc<Cleaner>,
+    format: ArchiveFormat,
 ) -> Result<Response, Error> {
     let mut name_occurrences = HashMap::new();
     for info in &mut files_info {
         let occurrence = name_occurrences.entry(info.name.clone()).or_insert(0u16);
         *occurrence += 1;
         if *occurrence >= 2 {
-            if let Some((name, extension)) = info.name.split_once('.') {
-                info.name = format!("{}-{}.{}", name, occurrence, extension);
-            } else {
-                info.name = format!("{}-{}", info.name, occurrence);
-            }
+            info.name = numbered_name(&info.name, *occurrence);
         }
     }
-    let archive_size = archive_size(
-        files_info
-            .iter()
-            .map(|f| (f.name.as_ref(), f.size as usize)),
-    );
 
+    let mut headers = HeaderMap::new();
     let (w, r) = duplex(64000);
-    tokio::spawn(async move {
-        let mut archive = Archive::new(w);
-        for info in files_info {
-            let mut fd = match dir.open_file(&info.id).await {
-                Ok(fd) => fd,
-                Err(err) => {
-                    log::error!("Failed to open file for archive streaming: {}", err);
-                    break;
-                }
-            };
-            match archive
-                .append(info.name, FileDateTime::now(), &mut fd)
-                .await
-            {
-                Ok(fd) => fd,
-                Err(err) => {
-                    log::error!("Failed to append file to archive: {}", err);
-                    break;
-                }
-            }
-            match super::file_downloaded(&pool, &dir, &info.id).await {
-                Ok(_) => (),
-                Err(err) => {
-                    log::error!("Failed to process file downloads counter update: {}", err);
-                    break;
-                }
-            }
+    let body: Box<dyn AsyncRead + Send + Unpin> = match format {
+        ArchiveFormat::Zip => {
+            let archive_size =
+                zip_size(files_info.iter().map(|f| (f.name.as_ref(), f.size as u64)));
+            headers.insert(CONTENT_LENGTH, HeaderValue::from(archive_size));
+            headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/zip"));
+            headers.insert(
+                CONTENT_DISPOSITION,
+                HeaderValue::from_static(r#"attachment; filename="archive.zip""#),
+            );
+            tokio::spawn(stream(pool, files_info, dir, cleaner, Zip::new(w)));
+            Box::new(r)
         }
-        match archive.finalize().await {
-            Ok(_) => (),
-            Err(err) => log::error!("Failed to write archive's completion data: {}", err),
+        ArchiveFormat::Tar => {
+            let archive_size =
+                tar_size(files_info.iter().map(|f| (f.name.as_ref(), f.size as u64)));
+            headers.insert(CONTENT_LENGTH, HeaderValue::from(archive_size));
+            headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/x-tar"));
+            headers.insert(
+                CONTENT_DISPOSITION,
+                HeaderValue::from_static(r#"attachment; filename="archive.tar""#),
+            );
+            tokio::spawn(stream(pool, files_info, dir, cleaner, Tar::new(w)));
+            Box::new(r)
         }
-    });
+        ArchiveFormat::TarGz => {
+            // Compressed size cannot be known in advance, stream it chunked.
+            headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/gzip"));
+            headers.insert(
+                CONTENT_DISPOSITION,
+                HeaderValue::from_static(r#"attachment; filename="archive.tar.gz""#),
+            );
+            tokio::spawn(stream(pool, files_info, dir, cleaner, Tar::new(w)));
+            Box::new(GzipEncoder::new(BufReader::new(r)))
+        }
+    };
 
     Ok((
         StatusCode::OK,
-        [
-            (CONTENT_LENGTH, HeaderValue::from(archive_size)),
-            (CONTENT_TYPE, HeaderValue::from_static("application/zip")),
-            (
-                CONTENT_DISPOSITION,
-                HeaderValue::from_static(r#"attachment; filename="archive.zip""#),
-            ),
-        ],
-        StreamBody::new(ReaderStream::new(r)),
+        headers,
+        StreamBody::new(ReaderStream::new(body)),
     )
         .into_response())
 }
+
+// Only the file name is numbered, leaving its directories untouched (e.g. dir/file-2.txt).
+fn numbered_name(name: &str, occurrence: u16) -> String {
+    let (dir, base) = match name.rsplit_once('/') {
+        Some((dir, base)) => (&name[..dir.len() + 1], base),
+        None => ("", name),
+    };
+    match base.split_once('.') {
+        Some((base, extension)) => format!("{}{}-{}.{}", dir, base, occurrence, extension),
+        None => format!("{}-{}", name, occurrence),
+    }
+}
+
+// Formats streamed one file after another, sharing the download accounting.
+#[async_trait]
+pub(super) trait Archive: Send {
+    async fn append_file<R>(
+        &mut self,
+        name: &str,
+        size: u64,
+        modified: u64,
+        reader: &mut R,
+    ) -> Result<(), IoError>
+    where
+        R: AsyncRead + Send + Unpin;
+
+    async fn finish(self) -> Result<(), IoError>;
+}
+
+async fn stream<A: Archive>(
+    pool: SqlitePool,
+    files_info: Vec<FileInfo>,
+    dir: Dir,
+    cleaner: Arc<Cleaner>,
+    mut archive: A,
+) {
+    for info in files_info {
+        let mut fd = match dir.open_decompressed(&info.id, info.compression).await {
+            Ok(fd) => fd,
+            Err(err) => {
+                log::error!("Failed to open file for archive streaming: {}", err);
+                break;
+            }
+        };
+        match archive
+            .append_file(&info.name, info.size as u64, info.modified(), &mut fd)
+            .await
+        {
+            Ok(_) => (),
+            Err(err) => {
+                log::error!("Failed to append file to archive: {}", err);
+                break;
+            }
+        }
+        match super::file_downloaded(&pool, &cleaner, &info.id).await {
+            Ok(_) => (),
+            Err(err) => {
+                log::error!("Failed to process file downloads counter update: {}", err);
+                break;
+            }
+        }
+    }
+    match archive.finish().await {
+        Ok(_) => (),
+        Err(err) => log::error!("Failed to write archive's completion data: {}", err),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{numbered_name, ArchiveFormat};
+
+    #[test]
+    fn numbered() {
+        assert_eq!(numbered_name("file.tar.gz", 2), "file-2.tar.gz");
+        assert_eq!(numbered_name("file", 3), "file-3");
+        assert_eq!(
+            numbered_name("dir.d/sub/file.txt", 2),
+            "dir.d/sub/file-2.txt"
+        );
+        assert_eq!(numbered_name("dir.d/file", 2), "dir.d/file-2");
+    }
+
+    #[test]
+    fn format_from_accept() {
+        assert_eq!(
+            ArchiveFormat::from_accept("application/x-tar"),
+            Some(ArchiveFormat::Tar)
+        );
+        assert_eq!(
+            ArchiveFormat::from_accept("text/html, application/gzip;q=0.9"),
+            Some(ArchiveFormat::TarGz)
+        );
+        assert_eq!(ArchiveFormat::from_accept("*/*"), None);
+    }
+}
@@ -1,82 +1,172 @@
 use std::{
+    io::Result as IoResult,
     pin::Pin,
+    sync::Arc,
     task::{Context, Poll},
 };
 
+use async_compression::tokio::bufread::GzipEncoder;
 use axum::{
     body::StreamBody,
     response::{IntoResponse, Response},
 };
-use futures::Stream;
 use hyper::{
-    header::{CONTENT_DISPOSITION, CONTENT_LENGTH, CONTENT_TYPE},
+    header::{
+        CONTENT_DISPOSITION, CONTENT_ENCODING, CONTENT_LENGTH, CONTENT_SECURITY_POLICY,
+        CONTENT_TYPE, VARY, X_CONTENT_TYPE_OPTIONS,
+    },
     http::HeaderValue,
-    StatusCode,
+    HeaderMap, StatusCode,
 };
 use percent_encoding::utf8_percent_encode;
 use sqlx::SqlitePool;
-use tokio::fs::File;
+use tokio::{
+    fs::File,
+    io::{AsyncRead, BufReader, ReadBuf},
+};
 use tokio_util::io::ReaderStream;
 
 use crate::{
-    download::FileInfo,
+    download::{preview::Preview, FileInfo},
     error::{download as DownloadError, Error},
-    storage::Dir,
+    storage::{Cleaner, Dir},
 };
 
 pub(super) async fn handler(
     pool: SqlitePool,
     info: &FileInfo,
     dir: Dir,
+    cleaner: Arc<Cleaner>,
+    accept_encoding: Option<&HeaderValue>,
+    inline: Option<Preview>,
 ) -> Result<Response, Error> {
     let fd = dir
         .open_file(&info.id)
         .await
         .map_err(|_| DownloadError::OpenFile)?;
-    let streamer = FileStreamer::new(fd, info, dir, pool);
+    let stored_size = fd
+        .metadata()
+        .await
+        .map_err(|_| DownloadError::OpenFile)?
+        .len();
+    let reader = FileReader::new(fd, stored_size, info, cleaner, pool);
 
-    Ok((
-        StatusCode::OK,
-        [
-            (CONTENT_LENGTH, HeaderValue::from(info.size as u64)),
-            (
+    let mut headers = HeaderMap::new();
+    let disposition = match (inline, info.mime.as_deref()) {
+        (Some(preview), Some(mime)) => {
+            headers.insert(
+                CONTENT_TYPE,
+                HeaderValue::try_from(mime).map_err(|_| DownloadError::OpenFile)?,
+            );
+            headers.insert(X_CONTENT_TYPE_OPTIONS, HeaderValue::from_static("nosniff"));
+            headers.insert(
+                CONTENT_SECURITY_POLICY,
+                HeaderValue::from_static(preview.content_security_policy()),
+            );
+            "inline"
+        }
+        _ => {
+            headers.insert(
                 CONTENT_TYPE,
                 HeaderValue::from_static("application/octet-stream"),
-            ),
-            (
-                CONTENT_DISPOSITION,
-                HeaderValue::try_from(format!(
-                    r#"attachment; filename*=UTF-8''{}; filename="{}""#,
-                    utf8_percent_encode(&info.name, percent_encoding::NON_ALPHANUMERIC),
-                    &info.name
-                ))
-                .map_err(|_| DownloadError::FilenameHeader)?,
-            ),
-        ],
-        StreamBody::new(streamer),
+            );
+            "attachment"
+        }
+    };
+    headers.insert(
+        CONTENT_DISPOSITION,
+        HeaderValue::try_from(format!(
+            r#"{}; filename*=UTF-8''{}; filename="{}""#,
+            disposition,
+            utf8_percent_encode(info.base_name(), percent_encoding::NON_ALPHANUMERIC),
+            info.base_name()
+        ))
+        .map_err(|_| DownloadError::FilenameHeader)?,
+    );
+
+    let body: Box<dyn AsyncRead + Send + Unpin> = match info.compression {
+        None => {
+            headers.insert(CONTENT_LENGTH, HeaderValue::from(info.size as u64));
+            Box::new(reader)
+        }
+        Some(compression) => {
+            headers.insert(VARY, HeaderValue::from_static("accept-encoding"));
+            if accepts_encoding(accept_encoding, compression.encoding()) {
+                // Serve stored bytes as is.
+                headers.insert(
+                    CONTENT_ENCODING,
+                    HeaderValue::from_static(compression.encoding()),
+                );
+                headers.insert(CONTENT_LENGTH, HeaderValue::from(stored_size));
+                Box::new(reader)
+            } else if accepts_encoding(accept_encoding, "gzip") {
+                // Re-encoded size is unknown, stream it chunked.
+                headers.insert(CONTENT_ENCODING, HeaderValue::from_static("gzip"));
+                Box::new(GzipEncoder::new(BufReader::new(
+                    compression.decoder(reader),
+                )))
+            } else {
+                headers.insert(CONTENT_LENGTH, HeaderValue::from(info.size as u64));
+                compression.decoder(reader)
+            }
+        }
+    };
+
+    Ok((
+        StatusCode::OK,
+        headers,
+        StreamBody::new(ReaderStream::new(body)),
     )
         .into_response())
 }
 
-struct FileStreamer {
-    streamed: usize,
-    total: usize,
+fn accepts_encoding(header: Option<&HeaderValue>, encoding: &str) -> bool {
+    let header = match header.and_then(|h| h.to_str().ok()) {
+        Some(header) => header,
+        None => return false,
+    };
+    header.split(',').any(|part| {
+        let mut params = part.split(';');
+        if !params
+            .next()
+            .map(|name| name.trim().eq_ignore_ascii_case(encoding))
+            .unwrap_or(false)
+        {
+            return false;
+        }
+        params
+            .find_map(|param| param.trim().strip_prefix("q="))
+            .map(|quality| quality.parse::<f32>().unwrap_or(0.0) > 0.0)
+            .unwrap_or(true)
+    })
+}
+
+// Tracks bytes read from the storage directory, whatever the encoding sent to the client.
+struct FileReader {
+    read: u64,
+    total: u64,
     decremented: bool,
-    file: ReaderStream<File>,
+    file: File,
     id: String,
-    dir: Dir,
+    cleaner: Arc<Cleaner>,
     pool: SqlitePool,
 }
 
-impl FileStreamer {
-    fn new(file: File, info: &FileInfo, dir: Dir, pool: SqlitePool) -> Self {
+impl FileReader {
+    fn new(
+        file: File,
+        total: u64,
+        info: &FileInfo,
+        cleaner: Arc<Cleaner>,
+        pool: SqlitePool,
+    ) -> Self {
         Self {
-            streamed: 0,
-            total: info.size as usize,
+            read: 0,
+            total,
             decremented: false,
-            file: ReaderStream::new(file),
+            file,
             id: info.id.clone(),
-            dir,
+            cleaner,
             pool,
         }
     }
@@ -84,10 +174,10 @@ impl FileStreamer {
     fn downloaded(&mut self) {
         self.decremented = true;
         let id = self.id.clone();
-        let dir = self.dir.clone();
+        let cleaner = Arc::clone(&self.cleaner);
         let pool = self.pool.clone();
         tokio::spawn(async move {
-            match super::file_downloaded(&pool, &dir, &id).await {
+            match super::file_downloaded(&pool, &cleaner, &id).await {
                 Ok(_) => (),
                 Err(err) => log::error!("Failed to process file downloads counter update: {}", err),
             }
@@ -95,17 +185,45 @@ impl FileStreamer {
     }
 }
 
-impl Stream for FileStreamer {
-    type Item = <ReaderStream<File> as Stream>::Item;
-
-    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
-        let polled = Pin::new(&mut self.file).poll_next(cx);
-        if let Poll::Ready(Some(Ok(data))) = &polled {
-            self.streamed += data.len();
-            if !self.decremented && self.streamed * 100 / self.total >= 95 {
-                self.downloaded();
+impl AsyncRead for FileReader {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<IoResult<()>> {
+        let before = buf.filled().len();
+        let polled = Pin::new(&mut self.file).poll_read(cx, buf);
+        if let Poll::Ready(Ok(())) = &polled {
+            let read = (buf.filled().len() - before) as u64;
+            if read > 0 {
+                self.read += read;
+                if !self.decremented && self.read * 100 / self.total >= 95 {
+                    self.downloaded();
+                }
             }
         }
         polled
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use hyper::http::HeaderValue;
+
+    use super::accepts_encoding;
+
+    #[test]
+    fn accept_encoding() {
+        let header = HeaderValue::from_static("gzip, deflate, br, zstd");
+        assert!(accepts_encoding(Some(&header), "zstd"));
+        assert!(accepts_encoding(Some(&header), "gzip"));
+
+        let header = HeaderValue::from_static("gzip;q=0.5, zstd;q=0");
+        assert!(accepts_encoding(Some(&header), "gzip"));
+        assert!(!accepts_encoding(Some(&header), "zstd"));
+
+        let header = HeaderValue::from_static("identity");
+        assert!(!accepts_encoding(Some(&header), "gzip"));
+        assert!(!accepts_encoding(None, "gzip"));
+    }
+}
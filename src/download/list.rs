@@ -0,0 +1,73 @@
+use std::sync::Arc;
+
+use axum::{
+    headers::{authorization::Basic, Authorization, Cookie},
+    Extension, TypedHeader,
+};
+use http_negotiator::{ContentTypeNegotiation, Negotiation};
+use itertools::Itertools;
+use serde::Serialize;
+use sqlx::SqlitePool;
+
+use crate::{
+    alias::AliasGroup,
+    auth::Authenticator,
+    error::Error,
+    response::{ApiHeader, ApiResponse, ResponseType, SingleLine},
+    upload::{Aliases, DomainUri, Links, Size},
+};
+
+#[derive(Serialize)]
+pub struct FileList {
+    files: Vec<ListedFile>,
+}
+
+#[derive(Serialize)]
+struct ListedFile {
+    name: String,
+    size: Size,
+    alias: Aliases,
+    link: Links,
+}
+
+impl ApiHeader for FileList {}
+
+impl SingleLine for FileList {
+    fn single_lined(&self) -> String {
+        self.files
+            .iter()
+            .map(|file| format!("{} {}", file.link.short, file.name))
+            .join("\n")
+    }
+}
+
+pub async fn handler(
+    Extension(pool): Extension<SqlitePool>,
+    response_type: Negotiation<ContentTypeNegotiation, ResponseType>,
+    authenticator: Extension<Arc<Authenticator>>,
+    auth_header: Option<TypedHeader<Authorization<Basic>>>,
+    cookie: Option<TypedHeader<Cookie>>,
+    AliasGroup(aliases): AliasGroup,
+    DomainUri(domain_uri): DomainUri,
+) -> Result<ApiResponse<FileList>, ApiResponse<Error>> {
+    super::authorize(&authenticator, auth_header, cookie)
+        .await
+        .map_err(|err| ApiResponse(*response_type, err))?;
+    let (files_info, _) = super::find_files(&pool, aliases)
+        .await
+        .map_err(|err| ApiResponse(*response_type, err))?;
+
+    let files = files_info
+        .into_iter()
+        .map(|info| {
+            let alias = (info.short_alias, info.long_alias);
+            ListedFile {
+                name: info.name,
+                size: Size::from(info.size as u64),
+                alias: Aliases::new(&alias),
+                link: Links::new(&domain_uri, &alias),
+            }
+        })
+        .collect();
+    Ok(ApiResponse(*response_type, FileList { files }))
+}
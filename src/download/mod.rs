@@ -1,39 +1,105 @@
-use std::sync::Arc;
+use std::{
+    sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
+};
 
 use axum::{
     extract::Query,
     headers::{authorization::Basic, Authorization, Cookie, UserAgent},
+    http::{
+        header::{ACCEPT, ACCEPT_ENCODING},
+        HeaderMap,
+    },
     response::IntoResponse,
     routing::get,
     Extension, Router, TypedHeader,
 };
 use serde::Deserialize;
-use sqlx::{FromRow, SqlitePool};
+use sqlx::{FromRow, SqliteConnection, SqlitePool};
 
 use crate::{
-    alias::AliasGroup,
+    alias::{Alias, AliasGroup},
     auth::{AuthStatus, Authenticator, Features},
+    collection::{collection_downloaded, Collection},
+    download::{
+        archive::ArchiveFormat,
+        preview::{accepts_html, Preview},
+    },
     error::{auth as AuthError, download as DownloadError, Error},
     include_query,
-    storage::Dir,
+    storage::{Cleaner, Compression, Dir},
+    upload::DomainUri,
 };
 
 mod archive;
 mod file;
+mod list;
 mod open_graph;
+mod paste;
+mod preview;
+mod qr;
+mod tar;
+mod thumbnail;
+mod zip;
+
+pub use open_graph::OpenGraph;
 
 #[derive(FromRow)]
 struct FileInfo {
     id: String,
     name: String,
     size: i64,
+    compression: Option<Compression>,
+    modified: Option<i64>,
+    mime: Option<String>,
+    language: Option<String>,
+    downloads: Option<i64>,
+    expiration: i64,
+    short_alias: String,
+    long_alias: String,
+    description: Option<String>,
+    tags: Option<String>,
+}
+
+impl FileInfo {
+    // Names may contain the relative directories of the file.
+    fn base_name(&self) -> &str {
+        self.name.rsplit('/').next().unwrap_or(&self.name)
+    }
+
+    fn has_alias(&self, alias: &Alias) -> bool {
+        match alias {
+            Alias::Short(alias) => &self.short_alias == alias,
+            Alias::Long(alias) => &self.long_alias == alias,
+        }
+    }
+
+    // Files uploaded before times were recorded are considered modified now.
+    fn modified(&self) -> u64 {
+        match self.modified {
+            Some(modified) => modified.max(0) as u64,
+            None => SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or_default(),
+        }
+    }
 }
 
 #[derive(Deserialize)]
 #[serde(rename_all = "kebab-case")]
-pub struct ForceDownload {
+pub struct DownloadQuery {
     #[serde(default)]
     force_download: bool,
+    #[serde(default)]
+    inline: bool,
+}
+
+#[derive(Deserialize)]
+pub struct ArchiveQuery {
+    format: Option<ArchiveFormat>,
+    // Comma separated aliases of the files to include.
+    select: Option<String>,
 }
 
 #[allow(clippy::too_many_arguments)]
@@ -43,10 +109,98 @@ pub async fn handler(
     auth_header: Option<TypedHeader<Authorization<Basic>>>,
     cookie: Option<TypedHeader<Cookie>>,
     AliasGroup(aliases): AliasGroup,
-    force_download: Query<ForceDownload>,
+    download_query: Query<DownloadQuery>,
+    archive_query: Query<ArchiveQuery>,
     user_agent: Option<TypedHeader<UserAgent>>,
+    headers: HeaderMap,
+    DomainUri(domain_uri): DomainUri,
     Extension(dir): Extension<Dir>,
+    Extension(cleaner): Extension<Arc<Cleaner>>,
+    Extension(open_graph): Extension<Arc<OpenGraph>>,
 ) -> Result<impl IntoResponse, Error> {
+    authorize(&authenticator, auth_header, cookie).await?;
+    let (mut files_info, collection) = find_files(&pool, aliases).await?;
+    if let Some(selection) = &archive_query.select {
+        files_info = select_files(files_info, selection)?;
+    }
+
+    if !download_query.force_download {
+        if let Some(user_agent) = user_agent {
+            if let Some(og_resp) = open_graph.proxy_request(
+                user_agent.as_str(),
+                &files_info,
+                collection.as_ref(),
+                &domain_uri,
+            ) {
+                return Ok(og_resp);
+            }
+        }
+    }
+
+    let format = archive_query
+        .format
+        .or_else(|| {
+            headers
+                .get(ACCEPT)
+                .and_then(|accept| accept.to_str().ok())
+                .and_then(ArchiveFormat::from_accept)
+        })
+        .unwrap_or(ArchiveFormat::Zip);
+    if let Some(collection) = collection {
+        if let Err(err) = collection_downloaded(&pool, &collection).await {
+            log::error!(
+                "Failed to process collection downloads counter update: {}",
+                err
+            );
+        }
+        return archive::handler(pool, files_info, dir, cleaner, format).await;
+    }
+
+    match files_info.len() {
+        0 => Err(DownloadError::AliasExtract),
+        1 => {
+            let info = &files_info[0];
+            if let Some(language) = &info.language {
+                if !download_query.force_download && accepts_html(&headers) {
+                    return paste::page(pool, info, dir, cleaner, language).await;
+                }
+            }
+            // A preview would consume one of the downloads of limited files.
+            let preview = info
+                .mime
+                .as_deref()
+                .and_then(Preview::from_mime)
+                .filter(|_| info.downloads.is_none());
+            match preview {
+                Some(preview) if download_query.inline => {
+                    file::handler(
+                        pool,
+                        info,
+                        dir,
+                        cleaner,
+                        headers.get(ACCEPT_ENCODING),
+                        Some(preview),
+                    )
+                    .await
+                }
+                Some(preview) if !download_query.force_download && accepts_html(&headers) => {
+                    Ok(preview::page(info, preview))
+                }
+                _ => {
+                    file::handler(pool, info, dir, cleaner, headers.get(ACCEPT_ENCODING), None)
+                        .await
+                }
+            }
+        }
+        _ => archive::handler(pool, files_info, dir, cleaner, format).await,
+    }
+}
+
+async fn authorize(
+    authenticator: &Authenticator,
+    auth_header: Option<TypedHeader<Authorization<Basic>>>,
+    cookie: Option<TypedHeader<Cookie>>,
+) -> Result<(), Error> {
     match authenticator
         .allows(
             auth_header.map(|h| h.0),
@@ -55,45 +209,91 @@ pub async fn handler(
         )
         .await
     {
-        AuthStatus::NotNeeded | AuthStatus::Valid(_) => (),
-        AuthStatus::Error(err) => return Err(err),
-        AuthStatus::Prompt => {
-            return Err(AuthError::MissingAuthorization);
-        }
-    };
+        AuthStatus::NotNeeded | AuthStatus::Valid(_) => Ok(()),
+        AuthStatus::Error(err) => Err(err),
+        AuthStatus::Prompt => Err(AuthError::MissingAuthorization),
+    }
+}
+
+async fn find_files(
+    pool: &SqlitePool,
+    aliases: Vec<Alias>,
+) -> Result<(Vec<FileInfo>, Option<Collection>), Error> {
     let mut conn = pool.acquire().await.map_err(|_| DownloadError::Database)?;
 
+    // A single alias may also designate a collection of files.
+    let single = aliases.len() == 1;
+    let mut collection = None;
     let mut files_info = Vec::with_capacity(aliases.len());
     for alias in aliases {
-        files_info.push(
-            sqlx::query_as::<_, FileInfo>(include_query!("get_file"))
-                .bind(alias.inner())
-                .bind(alias.inner())
-                .fetch_optional(&mut conn)
-                .await
-                .map_err(|_| DownloadError::Database)?
-                .ok_or(DownloadError::FileNotFound)?,
-        );
-    }
-
-    if !force_download.force_download {
-        if let Some(user_agent) = user_agent {
-            if let Some(og_resp) =
-                open_graph::proxy_request(user_agent.as_str().to_lowercase(), &files_info)
-            {
-                return Ok(og_resp);
+        match sqlx::query_as::<_, FileInfo>(include_query!("get_file"))
+            .bind(alias.inner())
+            .bind(alias.inner())
+            .fetch_optional(&mut conn)
+            .await
+            .map_err(|_| DownloadError::Database)?
+        {
+            Some(info) => files_info.push(info),
+            None if single => {
+                collection = Some(
+                    Collection::find(&alias, &mut conn)
+                        .await
+                        .map_err(|_| DownloadError::Database)?
+                        .ok_or(DownloadError::FileNotFound)?,
+                );
             }
+            None => return Err(DownloadError::FileNotFound),
+        }
+    }
+    if let Some(collection) = &collection {
+        files_info = sqlx::query_as::<_, FileInfo>(include_query!("get_collection_files"))
+            .bind(&collection.id)
+            .fetch_all(&mut conn)
+            .await
+            .map_err(|_| DownloadError::Database)?;
+        if files_info.is_empty() {
+            return Err(DownloadError::FileNotFound);
         }
     }
+    Ok((files_info, collection))
+}
 
-    match files_info.len() {
-        0 => Err(DownloadError::AliasExtract),
-        1 => file::handler(pool, &files_info[0], dir).await,
-        _ => archive::handler(pool, files_info, dir).await,
+// Keeps the original order of the group, and refuses files that are not part of it.
+fn select_files(files_info: Vec<FileInfo>, selection: &str) -> Result<Vec<FileInfo>, Error> {
+    let selection = selection
+        .split(',')
+        .map(|alias| alias.parse::<Alias>())
+        .collect::<Result<Vec<_>, _>>()?;
+    if selection
+        .iter()
+        .any(|alias| !files_info.iter().any(|info| info.has_alias(alias)))
+    {
+        return Err(DownloadError::InvalidSelection);
     }
+    Ok(files_info
+        .into_iter()
+        .filter(|info| selection.iter().any(|alias| info.has_alias(alias)))
+        .collect())
 }
 
-async fn file_downloaded(pool: &SqlitePool, dir: &Dir, id: &str) -> Result<(), String> {
+// Links always use short aliases, groups keep the order of their files.
+fn short_link(
+    domain_uri: &str,
+    files_info: &[FileInfo],
+    collection: Option<&Collection>,
+) -> String {
+    let alias = match collection {
+        Some(collection) => collection.short_alias.clone(),
+        None => files_info
+            .iter()
+            .map(|info| info.short_alias.as_str())
+            .collect::<Vec<_>>()
+            .join("+"),
+    };
+    format!("{}/{}", domain_uri, alias)
+}
+
+async fn file_downloaded(pool: &SqlitePool, cleaner: &Cleaner, id: &str) -> Result<(), String> {
     let mut conn = pool
         .acquire()
         .await
@@ -107,24 +307,10 @@ async fn file_downloaded(pool: &SqlitePool, dir: &Dir, id: &str) -> Result<(), S
     match downloads {
         None => (),
         Some(0) => return Err(format!("Found a zero downloads counter file: {}", id)),
-        Some(1) => {
-            dir.delete_file(id).await.map_err(|err| {
-                format!(
-                    "Failed to delete decremented to zero file from fs {}: {:?}",
-                    id, err
-                )
-            })?;
-            sqlx::query(include_query!("delete_file"))
-                .bind(id)
-                .execute(&mut conn)
-                .await
-                .map_err(|err| {
-                    format!(
-                        "Failed to delete decremented to zero file from database {}: {:?}",
-                        id, err
-                    )
-                })?;
-        }
+        Some(1) => cleaner
+            .remove_file(id, &mut conn)
+            .await
+            .map_err(|err| format!("Failed to remove decremented to zero file {}: {}", id, err))?,
         Some(count) => {
             sqlx::query(include_query!("update_file_downloads"))
                 .bind(count - 1)
@@ -136,13 +322,85 @@ async fn file_downloaded(pool: &SqlitePool, dir: &Dir, id: &str) -> Result<(), S
                 })?;
         }
     };
+    if downloads != Some(1) {
+        refresh_activity(&mut conn, id).await?;
+    }
     Ok(())
 }
 
-pub fn router(pool: SqlitePool, authenticator: Arc<Authenticator>, dir: Dir) -> Router {
+// Restarts the inactivity period of the file, if it has one.
+async fn refresh_activity(conn: &mut SqliteConnection, id: &str) -> Result<(), String> {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_err(|err| format!("Cannot generate timestamp: {}", err))?
+        .as_secs();
+    sqlx::query(include_query!("update_file_activity"))
+        .bind(now as i64)
+        .bind(id)
+        .execute(conn)
+        .await
+        .map_err(|err| format!("Failed to refresh file activity {}: {:?}", id, err))?;
+    Ok(())
+}
+
+pub fn router(
+    pool: SqlitePool,
+    authenticator: Arc<Authenticator>,
+    dir: Dir,
+    cleaner: Arc<Cleaner>,
+    open_graph: Arc<OpenGraph>,
+) -> Router {
     Router::new()
+        .route("/oembed", get(open_graph::oembed_handler))
         .route("/:alias", get(handler))
+        .route("/:alias/files", get(list::handler))
+        .route("/:alias/qr", get(qr::handler))
+        .route("/:alias/raw", get(paste::raw_handler))
+        .route("/:alias/thumbnail", get(thumbnail::handler))
         .route_layer(Extension(pool))
         .route_layer(Extension(authenticator))
         .route_layer(Extension(dir))
+        .route_layer(Extension(cleaner))
+        .route_layer(Extension(open_graph))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{select_files, FileInfo};
+
+    fn info(short_alias: &str, long_alias: &str) -> FileInfo {
+        FileInfo {
+            id: short_alias.to_owned(),
+            name: format!("{}.txt", short_alias),
+            size: 0,
+            compression: None,
+            modified: None,
+            mime: None,
+            language: None,
+            downloads: None,
+            expiration: 0,
+            short_alias: short_alias.to_owned(),
+            long_alias: long_alias.to_owned(),
+            description: None,
+            tags: None,
+        }
+    }
+
+    #[test]
+    fn selection() {
+        let files = || {
+            vec![
+                info("aaaaaa", "one-two-three"),
+                info("bbbbbb", "four-five-six"),
+                info("cccccc", "seven-eight-nine"),
+            ]
+        };
+        let selected = select_files(files(), "cccccc,one-two-three").unwrap();
+        assert_eq!(
+            selected.iter().map(|f| f.id.as_str()).collect::<Vec<_>>(),
+            ["aaaaaa", "cccccc"]
+        );
+        assert!(select_files(files(), "dddddd").is_err());
+        assert!(select_files(files(), "").is_err());
+    }
 }
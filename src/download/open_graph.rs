@@ -1,34 +1,247 @@
-use axum::response::{Html, IntoResponse, Response};
-use byte_unit::Byte;
+use std::{
+    sync::Arc,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
 
-use crate::download::FileInfo;
+use axum::{
+    extract::Query,
+    headers::{authorization::Basic, Authorization, Cookie},
+    response::{Html, IntoResponse, Response},
+    Extension, Json, TypedHeader,
+};
+use itertools::Itertools;
+use percent_encoding::{utf8_percent_encode, NON_ALPHANUMERIC};
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
 
-const BOTS: &[&str] = &["discord", "facebook", "twitter"];
+use crate::{
+    alias::AliasGroup,
+    auth::Authenticator,
+    collection::Collection,
+    download::{thumbnail::has_thumbnail, FileInfo},
+    error::{download as DownloadError, Error},
+    misc::{escape_html, fill_template, format_duration},
+    upload::{split_tags, DomainUri, Size},
+};
 
-pub(super) fn proxy_request(user_agent: String, files_info: &[FileInfo]) -> Option<Response> {
-    if BOTS.iter().all(|bot| !user_agent.contains(bot)) {
-        return None;
+// Link preview bots get a metadata page instead of the file itself.
+pub struct OpenGraph {
+    bots: Vec<String>,
+    template: String,
+}
+
+impl OpenGraph {
+    pub fn new(bots: &[String], template: Option<String>) -> Self {
+        Self {
+            bots: bots
+                .iter()
+                .map(|bot| bot.trim().to_lowercase())
+                .filter(|bot| !bot.is_empty())
+                .collect(),
+            template: template.unwrap_or_else(|| include_str!("redirect.html").to_owned()),
+        }
+    }
+
+    fn is_bot(&self, user_agent: &str) -> bool {
+        let user_agent = user_agent.to_lowercase();
+        self.bots.iter().any(|bot| user_agent.contains(bot))
     }
 
-    let title = match files_info.len() {
-        0 => return None,
-        1 => "Download File",
-        _ => "Download Archive",
-    };
+    pub(super) fn proxy_request(
+        &self,
+        user_agent: &str,
+        files_info: &[FileInfo],
+        collection: Option<&Collection>,
+        domain_uri: &str,
+    ) -> Option<Response> {
+        if !self.is_bot(user_agent) {
+            return None;
+        }
+        let summary = Summary::new(files_info, collection, domain_uri)?;
+
+        let mut description = files_info
+            .iter()
+            .map(|info| {
+                format!(
+                    "{} ({})",
+                    escape_html(&info.name),
+                    Size::from(info.size as u64).readable
+                )
+            })
+            .collect::<Vec<_>>();
+        description.push(format!("Expires in {}", summary.expires_in()));
+        // Notes of a single shared file come first, as chats usually truncate descriptions.
+        if let (None, [info]) = (collection, files_info) {
+            let tags = split_tags(info.tags.as_deref())
+                .iter()
+                .map(|tag| format!("#{}", escape_html(tag)))
+                .join(" ");
+            if !tags.is_empty() {
+                description.insert(0, tags);
+            }
+            if let Some(text) = &info.description {
+                description.insert(0, escape_html(text));
+            }
+        }
+        // Shared images are displayed as large cards.
+        let (image, card) = match &summary.thumbnail {
+            Some(thumbnail) => (thumbnail.clone(), "summary_large_image"),
+            None => (format!("{}/icon.png", domain_uri), "summary"),
+        };
+        let oembed = format!(
+            "{}/oembed?url={}&format=json",
+            domain_uri,
+            utf8_percent_encode(&summary.link, NON_ALPHANUMERIC)
+        );
+
+        let page = fill_template(
+            &self.template,
+            &[
+                ("$TITLE", &escape_html(&summary.title)),
+                ("$DESCRIPTION", &description.join("&#10;&#13;")),
+                ("$URL", &escape_html(&summary.link)),
+                ("$IMAGE", &escape_html(&image)),
+                ("$CARD", card),
+                ("$SIZE", &Size::from(summary.size).readable),
+                ("$EXPIRATION", &summary.expires_in()),
+                ("$OEMBED", &escape_html(&oembed)),
+            ],
+        );
+        Some(Html(page).into_response())
+    }
+}
 
-    let description = files_info
-        .iter()
-        .map(|info| {
-            format!(
-                "{} ({})",
-                info.name,
-                Byte::from_bytes(info.size as u64).get_appropriate_unit(false)
-            )
+struct Summary {
+    title: String,
+    link: String,
+    size: u64,
+    expiration: i64,
+    thumbnail: Option<String>,
+}
+
+impl Summary {
+    fn new(
+        files_info: &[FileInfo],
+        collection: Option<&Collection>,
+        domain_uri: &str,
+    ) -> Option<Self> {
+        let title = match (collection, files_info) {
+            (_, []) => return None,
+            (Some(collection), _) => collection.name.clone(),
+            (None, [info]) => info.base_name().to_owned(),
+            (None, _) => format!("{} files", files_info.len()),
+        };
+        // A group disappears as soon as one of its files does.
+        let expiration = match collection {
+            Some(collection) => collection.expiration,
+            None => files_info.iter().map(|info| info.expiration).min()?,
+        };
+        let thumbnail = match (collection, files_info) {
+            (None, [info]) if has_thumbnail(info) => {
+                Some(format!("{}/{}/thumbnail", domain_uri, info.short_alias))
+            }
+            _ => None,
+        };
+        Some(Self {
+            title,
+            link: super::short_link(domain_uri, files_info, collection),
+            size: files_info.iter().map(|info| info.size as u64).sum(),
+            expiration,
+            thumbnail,
         })
-        .collect::<Vec<_>>();
-    let page = include_str!("redirect.html")
-        .replacen("$TITLE", title, 1)
-        .replacen("$DESCRIPTION", &description.join("&#10;&#13;"), 1);
+    }
+
+    fn remaining(&self) -> u64 {
+        (self.expiration.max(0) as u64).saturating_sub(
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or_default(),
+        )
+    }
+
+    fn expires_in(&self) -> String {
+        format_duration(Duration::from_secs(self.remaining()))
+    }
+}
+
+#[derive(Deserialize)]
+pub struct OEmbedQuery {
+    url: String,
+    format: Option<String>,
+}
+
+// https://oembed.com/#section2.3
+#[derive(Serialize)]
+pub struct OEmbed {
+    version: &'static str,
+    #[serde(rename = "type")]
+    kind: &'static str,
+    title: String,
+    provider_name: &'static str,
+    provider_url: String,
+    cache_age: u64,
+}
+
+pub async fn oembed_handler(
+    Extension(pool): Extension<SqlitePool>,
+    authenticator: Extension<Arc<Authenticator>>,
+    auth_header: Option<TypedHeader<Authorization<Basic>>>,
+    cookie: Option<TypedHeader<Cookie>>,
+    Query(query): Query<OEmbedQuery>,
+    DomainUri(domain_uri): DomainUri,
+) -> Result<Json<OEmbed>, Error> {
+    super::authorize(&authenticator, auth_header, cookie).await?;
+    if query.format.as_deref().unwrap_or("json") != "json" {
+        return Err(DownloadError::UnsupportedOEmbedFormat);
+    }
+    let AliasGroup(aliases) = url_alias(&query.url)
+        .and_then(|alias| alias.parse().ok())
+        .ok_or(DownloadError::FileNotFound)?;
+    let (files_info, collection) = super::find_files(&pool, aliases).await?;
+    let summary = Summary::new(&files_info, collection.as_ref(), &domain_uri)
+        .ok_or(DownloadError::FileNotFound)?;
 
-    Some(Html(page).into_response())
+    Ok(Json(OEmbed {
+        version: "1.0",
+        kind: "link",
+        title: summary.title.clone(),
+        provider_name: "Dropit",
+        provider_url: domain_uri,
+        cache_age: summary.remaining(),
+    }))
+}
+
+// Only the first path segment is used, the host may differ when behind a reverse proxy.
+fn url_alias(url: &str) -> Option<&str> {
+    let url = url.split_once("://").map(|(_, rest)| rest).unwrap_or(url);
+    let (_, path) = url.split(['?', '#']).next()?.split_once('/')?;
+    path.split('/').next().filter(|alias| !alias.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{url_alias, OpenGraph};
+
+    #[test]
+    fn bots() {
+        let open_graph = OpenGraph::new(&["Slackbot".to_owned(), " ".to_owned()], None);
+        assert!(open_graph.is_bot("Slackbot-LinkExpanding 1.0 (+https://api.slack.com/robots)"));
+        assert!(!open_graph.is_bot("Mozilla/5.0 (X11; Linux x86_64; rv:106.0) Firefox/106.0"));
+    }
+
+    #[test]
+    fn alias() {
+        assert_eq!(url_alias("https://drop.it/aBcDeF"), Some("aBcDeF"));
+        assert_eq!(
+            url_alias("http://drop.it:8080/aaaaaa+bbbbbb?force-download=true"),
+            Some("aaaaaa+bbbbbb")
+        );
+        assert_eq!(
+            url_alias("drop.it/one-two-three/files#top"),
+            Some("one-two-three")
+        );
+        assert_eq!(url_alias("https://drop.it/"), None);
+        assert_eq!(url_alias("https://drop.it"), None);
+    }
 }
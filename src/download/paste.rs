@@ -0,0 +1,160 @@
+use std::sync::Arc;
+
+use axum::{
+    headers::{authorization::Basic, Authorization, Cookie},
+    http::{header::ACCEPT_ENCODING, HeaderMap},
+    response::{Html, IntoResponse, Response},
+    Extension, TypedHeader,
+};
+use lazy_static::lazy_static;
+use sqlx::SqlitePool;
+use syntect::{
+    easy::HighlightLines,
+    highlighting::{Theme, ThemeSet},
+    html::{styled_line_to_highlighted_html, IncludeBackground},
+    parsing::SyntaxSet,
+    util::LinesWithEndings,
+};
+use tokio::io::AsyncReadExt;
+
+use crate::{
+    alias::Alias,
+    auth::Authenticator,
+    download::{file, preview::Preview, FileInfo},
+    error::{download as DownloadError, Error},
+    misc::{escape_html, fill_template},
+    storage::{Cleaner, Dir},
+    upload::Size,
+};
+
+lazy_static! {
+    static ref SYNTAXES: SyntaxSet = SyntaxSet::load_defaults_newlines();
+    static ref THEME: Theme = ThemeSet::load_defaults()
+        .themes
+        .remove("base16-ocean.dark")
+        .expect("missing default theme");
+}
+
+// Viewing a paste delivers its whole content, so it counts as a download.
+pub(super) async fn page(
+    pool: SqlitePool,
+    info: &FileInfo,
+    dir: Dir,
+    cleaner: Arc<Cleaner>,
+    language: &str,
+) -> Result<Response, Error> {
+    let mut content = Vec::with_capacity(info.size as usize);
+    dir.open_decompressed(&info.id, info.compression)
+        .await
+        .map_err(|_| DownloadError::OpenFile)?
+        .read_to_end(&mut content)
+        .await
+        .map_err(|_| DownloadError::OpenFile)?;
+    let language = language.to_owned();
+    let (syntax, lines) = tokio::task::spawn_blocking(move || {
+        highlight(&String::from_utf8_lossy(&content), &language)
+    })
+    .await
+    .map_err(|_| DownloadError::OpenFile)?;
+    if let Err(err) = super::file_downloaded(&pool, &cleaner, &info.id).await {
+        log::error!("Failed to process file downloads counter update: {}", err);
+    }
+
+    let rows = lines
+        .iter()
+        .enumerate()
+        .map(|(index, line)| {
+            format!(
+                r##"        <tr id="L{0}"><td class="number"><a href="#L{0}">{0}</a></td><td class="line">{1}</td></tr>"##,
+                index + 1,
+                line,
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    let description = format!(
+        "{}, {}, {} line(s)",
+        escape_html(&syntax),
+        Size::from(info.size as u64).readable,
+        lines.len(),
+    );
+
+    let page = fill_template(
+        include_str!("paste.html"),
+        &[
+            ("$TITLE", &escape_html(info.base_name())),
+            ("$DESCRIPTION", &description),
+            ("$ALIAS", &info.short_alias),
+            ("$NOTES", &super::preview::notes(info)),
+            ("$LINES", &rows),
+        ],
+    );
+    Ok(Html(page).into_response())
+}
+
+#[allow(clippy::too_many_arguments)]
+pub async fn raw_handler(
+    Extension(pool): Extension<SqlitePool>,
+    authenticator: Extension<Arc<Authenticator>>,
+    auth_header: Option<TypedHeader<Authorization<Basic>>>,
+    cookie: Option<TypedHeader<Cookie>>,
+    alias: Alias,
+    headers: HeaderMap,
+    Extension(dir): Extension<Dir>,
+    Extension(cleaner): Extension<Arc<Cleaner>>,
+) -> Result<Response, Error> {
+    super::authorize(&authenticator, auth_header, cookie).await?;
+    let (files_info, collection) = super::find_files(&pool, vec![alias]).await?;
+    match files_info.first() {
+        Some(info) if collection.is_none() && info.language.is_some() => {
+            file::handler(
+                pool,
+                info,
+                dir,
+                cleaner,
+                headers.get(ACCEPT_ENCODING),
+                Some(Preview::Text),
+            )
+            .await
+        }
+        _ => Err(DownloadError::FileNotFound),
+    }
+}
+
+// Returns the name of the syntax used and each line as HTML, without its line ending.
+fn highlight(content: &str, language: &str) -> (String, Vec<String>) {
+    let syntax = SYNTAXES
+        .find_syntax_by_token(language)
+        .unwrap_or_else(|| SYNTAXES.find_syntax_plain_text());
+    let mut highlighter = HighlightLines::new(syntax, &THEME);
+    let lines = LinesWithEndings::from(content)
+        .map(|line| {
+            highlighter
+                .highlight_line(line, &SYNTAXES)
+                .ok()
+                .and_then(|regions| {
+                    styled_line_to_highlighted_html(&regions, IncludeBackground::No).ok()
+                })
+                .unwrap_or_else(|| escape_html(line))
+                .replace(['\r', '\n'], "")
+        })
+        .collect();
+    (syntax.name.clone(), lines)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::highlight;
+
+    #[test]
+    fn highlighted() {
+        let (syntax, lines) = highlight("fn main() {\n    let s = \"<b>\";\n}\n", "rs");
+        assert_eq!(syntax, "Rust");
+        assert_eq!(lines.len(), 3);
+        assert!(lines[1].contains("&lt;b&gt;"));
+        assert!(lines.iter().all(|line| !line.contains('\n')));
+
+        let (syntax, _) = highlight("hello", "unknown");
+        assert_eq!(syntax, "Plain Text");
+    }
+}
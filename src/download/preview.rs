@@ -0,0 +1,137 @@
+use axum::response::{Html, IntoResponse, Response};
+use hyper::{header::ACCEPT, HeaderMap};
+
+use crate::{
+    download::FileInfo,
+    misc::{escape_html, fill_template},
+    upload::{split_tags, Size},
+};
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub(super) enum Preview {
+    Image,
+    Video,
+    Audio,
+    Pdf,
+    Text,
+}
+
+impl Preview {
+    // Only types that browsers display without running any of the file's content.
+    pub(super) fn from_mime(mime: &str) -> Option<Self> {
+        match mime.split(';').next().unwrap_or_default().trim() {
+            "image/png" | "image/jpeg" | "image/gif" | "image/webp" | "image/avif" => {
+                Some(Self::Image)
+            }
+            "video/mp4" | "video/webm" | "video/quicktime" => Some(Self::Video),
+            "audio/mpeg" | "audio/ogg" | "audio/flac" | "audio/wav" | "audio/mp4" => {
+                Some(Self::Audio)
+            }
+            "application/pdf" => Some(Self::Pdf),
+            "text/plain" => Some(Self::Text),
+            _ => None,
+        }
+    }
+
+    // Browsers refuse to render PDFs in a sandboxed document, so they only get a restrictive policy.
+    pub(super) fn content_security_policy(&self) -> &'static str {
+        match self {
+            Self::Pdf => "default-src 'none'; object-src 'self'",
+            _ => "sandbox; default-src 'none'; img-src 'self'; media-src 'self'; style-src 'unsafe-inline'",
+        }
+    }
+
+    fn element(&self) -> &'static str {
+        match self {
+            Self::Image => r#"<img src="?inline=true" alt="$TITLE">"#,
+            Self::Video => r#"<video src="?inline=true" controls></video>"#,
+            Self::Audio => r#"<audio src="?inline=true" controls></audio>"#,
+            Self::Pdf => r#"<iframe src="?inline=true" title="$TITLE"></iframe>"#,
+            Self::Text => r#"<iframe src="?inline=true" title="$TITLE" sandbox></iframe>"#,
+        }
+    }
+}
+
+// Command line clients (e.g. curl) do not ask for HTML and keep receiving the file itself.
+pub(super) fn accepts_html(headers: &HeaderMap) -> bool {
+    headers
+        .get(ACCEPT)
+        .and_then(|accept| accept.to_str().ok())
+        .map(|accept| {
+            accept
+                .split(',')
+                .any(|media| media.split(';').next().unwrap_or_default().trim() == "text/html")
+        })
+        .unwrap_or(false)
+}
+
+// Description and tags given by the uploader, if any.
+pub(super) fn notes(info: &FileInfo) -> String {
+    let description = info
+        .description
+        .as_deref()
+        .map(|description| format!(r#"<p class="description">{}</p>"#, escape_html(description)))
+        .unwrap_or_default();
+    let tags = split_tags(info.tags.as_deref())
+        .iter()
+        .map(|tag| format!(r#"<span class="tag">#{}</span>"#, escape_html(tag)))
+        .collect::<String>();
+    match (description.is_empty(), tags.is_empty()) {
+        (true, true) => String::new(),
+        (_, true) => format!(r#"<div class="notes">{}</div>"#, description),
+        _ => format!(
+            r#"<div class="notes">{}<div class="tags">{}</div></div>"#,
+            description, tags
+        ),
+    }
+}
+
+pub(super) fn page(info: &FileInfo, preview: Preview) -> Response {
+    let title = escape_html(info.base_name());
+    let description = format!(
+        "{}, {}",
+        Size::from(info.size as u64).readable,
+        escape_html(info.mime.as_deref().unwrap_or_default())
+    );
+    let page = fill_template(
+        include_str!("preview.html"),
+        &[
+            ("$PREVIEW", preview.element()),
+            ("$TITLE", &title),
+            ("$DESCRIPTION", &description),
+            ("$NOTES", &notes(info)),
+        ],
+    );
+    Html(page).into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use hyper::{header::ACCEPT, http::HeaderValue, HeaderMap};
+
+    use super::{accepts_html, Preview};
+
+    #[test]
+    fn from_mime() {
+        assert_eq!(Preview::from_mime("image/png"), Some(Preview::Image));
+        assert_eq!(
+            Preview::from_mime("text/plain; charset=utf-8"),
+            Some(Preview::Text)
+        );
+        assert_eq!(Preview::from_mime("application/zip"), None);
+        assert_eq!(Preview::from_mime("image/svg+xml"), None);
+    }
+
+    #[test]
+    fn html() {
+        let mut headers = HeaderMap::new();
+        assert!(!accepts_html(&headers));
+        headers.insert(ACCEPT, HeaderValue::from_static("*/*"));
+        assert!(!accepts_html(&headers));
+        headers.insert(
+            ACCEPT,
+            HeaderValue::from_static("text/html,application/xhtml+xml;q=0.9,*/*;q=0.8"),
+        );
+        assert!(accepts_html(&headers));
+    }
+}
@@ -0,0 +1,106 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::Query,
+    headers::{authorization::Basic, Authorization, Cookie},
+    http::{
+        header::{ACCEPT, CONTENT_TYPE},
+        HeaderMap, HeaderValue,
+    },
+    response::{IntoResponse, Response},
+    Extension, TypedHeader,
+};
+use hyper::StatusCode;
+use serde::Deserialize;
+use sqlx::SqlitePool;
+
+use crate::{
+    alias::AliasGroup,
+    auth::Authenticator,
+    error::{download as DownloadError, Error},
+    qr::Qr,
+    upload::DomainUri,
+};
+
+#[derive(Deserialize, Copy, Clone, PartialEq, Eq, Debug)]
+#[serde(rename_all = "lowercase")]
+enum QrFormat {
+    Png,
+    Svg,
+    Text,
+}
+
+impl QrFormat {
+    fn from_accept(accept: &str) -> Option<Self> {
+        accept.split(',').find_map(|media| {
+            match media.split(';').next().unwrap_or_default().trim() {
+                "image/png" => Some(Self::Png),
+                "image/svg+xml" => Some(Self::Svg),
+                "text/plain" => Some(Self::Text),
+                _ => None,
+            }
+        })
+    }
+}
+
+#[derive(Deserialize)]
+pub struct QrQuery {
+    format: Option<QrFormat>,
+}
+
+#[allow(clippy::too_many_arguments)]
+pub async fn handler(
+    Extension(pool): Extension<SqlitePool>,
+    authenticator: Extension<Arc<Authenticator>>,
+    auth_header: Option<TypedHeader<Authorization<Basic>>>,
+    cookie: Option<TypedHeader<Cookie>>,
+    AliasGroup(aliases): AliasGroup,
+    Query(query): Query<QrQuery>,
+    headers: HeaderMap,
+    DomainUri(domain_uri): DomainUri,
+) -> Result<Response, Error> {
+    super::authorize(&authenticator, auth_header, cookie).await?;
+    let (files_info, collection) = super::find_files(&pool, aliases).await?;
+    let qr = Qr::new(&super::short_link(
+        &domain_uri,
+        &files_info,
+        collection.as_ref(),
+    ))
+    .ok_or(DownloadError::QrCode)?;
+
+    let format = query
+        .format
+        .or_else(|| {
+            headers
+                .get(ACCEPT)
+                .and_then(|accept| accept.to_str().ok())
+                .and_then(QrFormat::from_accept)
+        })
+        .unwrap_or(QrFormat::Png);
+    let (content_type, body) = match format {
+        QrFormat::Png => ("image/png", qr.png().ok_or(DownloadError::QrCode)?),
+        QrFormat::Svg => ("image/svg+xml", qr.svg().into_bytes()),
+        QrFormat::Text => ("text/plain; charset=utf-8", qr.text().into_bytes()),
+    };
+    Ok((
+        StatusCode::OK,
+        [(CONTENT_TYPE, HeaderValue::from_static(content_type))],
+        body,
+    )
+        .into_response())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::QrFormat;
+
+    #[test]
+    fn from_accept() {
+        assert_eq!(QrFormat::from_accept("image/svg+xml"), Some(QrFormat::Svg));
+        assert_eq!(
+            QrFormat::from_accept("text/plain;q=0.9, */*"),
+            Some(QrFormat::Text)
+        );
+        assert_eq!(QrFormat::from_accept("*/*"), None);
+    }
+}
@@ -0,0 +1,163 @@
+use std::io::{Error as IoError, ErrorKind};
+
+use async_trait::async_trait;
+use tokio::io::{self, AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+use crate::download::archive::Archive;
+
+const BLOCK_SIZE: u64 = 512;
+const NAME_SIZE: usize = 100;
+// Largest size representable using the 11 octal digits of the size field.
+const MAX_OCTAL_SIZE: u64 = 0o77777777777;
+
+pub(super) struct Tar<W> {
+    writer: W,
+}
+
+impl<W: AsyncWrite + Unpin> Tar<W> {
+    pub(super) fn new(writer: W) -> Self {
+        Self { writer }
+    }
+
+    pub(super) async fn append<R>(
+        &mut self,
+        name: &str,
+        size: u64,
+        modified: u64,
+        reader: &mut R,
+    ) -> Result<(), IoError>
+    where
+        R: AsyncRead + Unpin,
+    {
+        // Use a GNU long name entry if the name doesn't fit in the header.
+        if name.len() >= NAME_SIZE {
+            let long_name = [name.as_bytes(), &[0]].concat();
+            self.writer
+                .write_all(&header("././@LongLink", long_name.len() as u64, 0, b'L'))
+                .await?;
+            self.writer.write_all(&long_name).await?;
+            self.pad(long_name.len() as u64).await?;
+        }
+        self.writer
+            .write_all(&header(name, size, modified, b'0'))
+            .await?;
+
+        let copied = io::copy(&mut reader.take(size), &mut self.writer).await?;
+        if copied != size {
+            return Err(IoError::new(
+                ErrorKind::UnexpectedEof,
+                "file shorter than expected",
+            ));
+        }
+        self.pad(size).await
+    }
+
+    pub(super) async fn finalize(mut self) -> Result<(), IoError> {
+        self.writer.write_all(&[0; 2 * BLOCK_SIZE as usize]).await?;
+        self.writer.shutdown().await
+    }
+
+    async fn pad(&mut self, size: u64) -> Result<(), IoError> {
+        let padding = padded(size) - size;
+        self.writer.write_all(&vec![0; padding as usize]).await
+    }
+}
+
+#[async_trait]
+impl<W: AsyncWrite + Send + Unpin> Archive for Tar<W> {
+    async fn append_file<R>(
+        &mut self,
+        name: &str,
+        size: u64,
+        modified: u64,
+        reader: &mut R,
+    ) -> Result<(), IoError>
+    where
+        R: AsyncRead + Send + Unpin,
+    {
+        self.append(name, size, modified, reader).await
+    }
+
+    async fn finish(self) -> Result<(), IoError> {
+        self.finalize().await
+    }
+}
+
+pub(super) fn tar_size<'a, I>(files: I) -> u64
+where
+    I: IntoIterator<Item = (&'a str, u64)>,
+{
+    files
+        .into_iter()
+        .map(|(name, size)| {
+            let long_name = if name.len() >= NAME_SIZE {
+                BLOCK_SIZE + padded(name.len() as u64 + 1)
+            } else {
+                0
+            };
+            long_name + BLOCK_SIZE + padded(size)
+        })
+        .sum::<u64>()
+        + 2 * BLOCK_SIZE
+}
+
+fn padded(size: u64) -> u64 {
+    size.div_ceil(BLOCK_SIZE) * BLOCK_SIZE
+}
+
+fn header(name: &str, size: u64, modified: u64, kind: u8) -> [u8; BLOCK_SIZE as usize] {
+    let mut header = [0; BLOCK_SIZE as usize];
+    let name = name.as_bytes();
+    let name_len = name.len().min(NAME_SIZE);
+    header[..name_len].copy_from_slice(&name[..name_len]);
+    header[100..108].copy_from_slice(b"0000644\0");
+    header[108..116].copy_from_slice(b"0000000\0");
+    header[116..124].copy_from_slice(b"0000000\0");
+    if size <= MAX_OCTAL_SIZE {
+        header[124..136].copy_from_slice(format!("{:011o}\0", size).as_bytes());
+    } else {
+        // GNU base-256 encoding for files larger than 8 GiB.
+        header[124] = 0x80;
+        header[128..136].copy_from_slice(&size.to_be_bytes());
+    }
+    header[136..148].copy_from_slice(format!("{:011o}\0", modified.min(MAX_OCTAL_SIZE)).as_bytes());
+    header[156] = kind;
+    header[257..263].copy_from_slice(b"ustar\0");
+    header[263..265].copy_from_slice(b"00");
+
+    // Checksum is computed with its own field filled with spaces.
+    header[148..156].copy_from_slice(b"        ");
+    let checksum = header.iter().map(|&b| b as u32).sum::<u32>();
+    header[148..156].copy_from_slice(format!("{:06o}\0 ", checksum).as_bytes());
+    header
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{tar_size, Tar};
+
+    #[tokio::test]
+    async fn size() {
+        let long_name = "long-".repeat(30);
+        let files = [
+            ("empty", Vec::new()),
+            ("small.txt", b"hello".to_vec()),
+            ("block", vec![1; 512]),
+            (long_name.as_str(), vec![2; 513]),
+        ];
+        let mut output = Vec::new();
+        let mut tar = Tar::new(&mut output);
+        for (name, content) in &files {
+            tar.append(name, content.len() as u64, 0, &mut content.as_slice())
+                .await
+                .unwrap();
+        }
+        tar.finalize().await.unwrap();
+
+        assert_eq!(
+            output.len() as u64,
+            tar_size(files.iter().map(|(n, c)| (*n, c.len() as u64)))
+        );
+        assert_eq!(output.len() % 512, 0);
+    }
+}
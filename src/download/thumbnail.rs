@@ -0,0 +1,124 @@
+use std::{io::Cursor, sync::Arc};
+
+use axum::{
+    headers::{authorization::Basic, Authorization, Cookie},
+    http::header::{CACHE_CONTROL, CONTENT_TYPE},
+    response::{IntoResponse, Response},
+    Extension, TypedHeader,
+};
+use hyper::{http::HeaderValue, StatusCode};
+use image::{io::Reader, ImageOutputFormat};
+use sqlx::SqlitePool;
+use tokio::io::AsyncReadExt;
+
+use crate::{
+    alias::Alias,
+    auth::{Authenticator, Features},
+    download::{preview::Preview, FileInfo},
+    error::{download as DownloadError, Error},
+    storage::Dir,
+};
+
+const THUMBNAIL_SIZE: u32 = 600;
+// Larger images would take too long to decode for a chat preview.
+const SOURCE_MAX_SIZE: i64 = 32 * 1024 * 1024;
+
+// Only images get thumbnails, rendering PDFs would require a native library.
+pub(super) fn has_thumbnail(info: &FileInfo) -> bool {
+    info.mime.as_deref().and_then(Preview::from_mime) == Some(Preview::Image)
+        && info.size <= SOURCE_MAX_SIZE
+        && info.downloads.is_none()
+}
+
+// Thumbnails are generated on first request, then cached next to their file.
+pub async fn handler(
+    Extension(pool): Extension<SqlitePool>,
+    authenticator: Extension<Arc<Authenticator>>,
+    auth_header: Option<TypedHeader<Authorization<Basic>>>,
+    cookie: Option<TypedHeader<Cookie>>,
+    alias: Alias,
+    Extension(dir): Extension<Dir>,
+) -> Result<Response, Error> {
+    super::authorize(&authenticator, auth_header, cookie).await?;
+    let (files_info, collection) = super::find_files(&pool, vec![alias]).await?;
+    let info = match files_info.first() {
+        Some(info) if collection.is_none() && has_thumbnail(info) => info,
+        _ => return Err(DownloadError::FileNotFound),
+    };
+
+    let thumbnail = match dir.read_thumbnail(&info.id).await {
+        Ok(thumbnail) => thumbnail,
+        Err(_) => {
+            let thumbnail = generate_thumbnail(info, &dir).await?;
+            if let Err(err) = dir.write_thumbnail(&info.id, &thumbnail).await {
+                log::error!("Failed to cache thumbnail of file {}: {}", info.id, err);
+            }
+            thumbnail
+        }
+    };
+    // Shared caches must not serve thumbnails of protected files to unauthenticated clients.
+    let cache_control = if authenticator.protects(Features::DOWNLOAD) {
+        "private, max-age=86400"
+    } else {
+        "public, max-age=86400"
+    };
+    Ok((
+        StatusCode::OK,
+        [
+            (CONTENT_TYPE, HeaderValue::from_static("image/png")),
+            (CACHE_CONTROL, HeaderValue::from_static(cache_control)),
+        ],
+        thumbnail,
+    )
+        .into_response())
+}
+
+async fn generate_thumbnail(info: &FileInfo, dir: &Dir) -> Result<Vec<u8>, Error> {
+    let mut source = Vec::with_capacity(info.size as usize);
+    dir.open_decompressed(&info.id, info.compression)
+        .await
+        .map_err(|_| DownloadError::OpenFile)?
+        .read_to_end(&mut source)
+        .await
+        .map_err(|_| DownloadError::OpenFile)?;
+    tokio::task::spawn_blocking(move || thumbnail(&source))
+        .await
+        .ok()
+        .flatten()
+        .ok_or(DownloadError::FileNotFound)
+}
+
+fn thumbnail(source: &[u8]) -> Option<Vec<u8>> {
+    let image = Reader::new(Cursor::new(source))
+        .with_guessed_format()
+        .ok()?
+        .decode()
+        .ok()?;
+    let mut thumbnail = Vec::new();
+    image
+        .thumbnail(THUMBNAIL_SIZE, THUMBNAIL_SIZE)
+        .write_to(&mut Cursor::new(&mut thumbnail), ImageOutputFormat::Png)
+        .ok()?;
+    Some(thumbnail)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use image::{DynamicImage, ImageOutputFormat};
+
+    use super::thumbnail;
+
+    #[test]
+    fn resized() {
+        let mut source = Vec::new();
+        DynamicImage::new_rgb8(1920, 1080)
+            .write_to(&mut Cursor::new(&mut source), ImageOutputFormat::Png)
+            .unwrap();
+        let resized = image::load_from_memory(&thumbnail(&source).unwrap()).unwrap();
+        assert_eq!((resized.width(), resized.height()), (600, 338));
+
+        assert!(thumbnail(b"not an image").is_none());
+    }
+}
@@ -0,0 +1,405 @@
+use std::io::{Error as IoError, ErrorKind};
+
+use async_trait::async_trait;
+use crc32fast::Hasher;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+use crate::download::archive::Archive;
+
+const BUFFER_SIZE: usize = 64 * 1024;
+// Values above these limits are moved to the ZIP64 extra field or end records.
+const MAX_U16: u64 = 0xFFFF;
+const MAX_U32: u64 = 0xFFFF_FFFF;
+
+const LOCAL_HEADER_SIGNATURE: u32 = 0x04034b50;
+const DATA_DESCRIPTOR_SIGNATURE: u32 = 0x08074b50;
+const CENTRAL_HEADER_SIGNATURE: u32 = 0x02014b50;
+const ZIP64_END_SIGNATURE: u32 = 0x06064b50;
+const ZIP64_LOCATOR_SIGNATURE: u32 = 0x07064b50;
+const END_SIGNATURE: u32 = 0x06054b50;
+
+// Unix host, specification 4.5.
+const VERSION_MADE_BY: u16 = 0x0300 | 45;
+const VERSION_DEFAULT: u16 = 20;
+const VERSION_ZIP64: u16 = 45;
+// CRC and sizes are written after the data, names are UTF-8.
+const FLAGS: u16 = 0x0008 | 0x0800;
+const ZIP64_EXTRA_ID: u16 = 0x0001;
+const TIMESTAMP_EXTRA_ID: u16 = 0x5455;
+const FILE_MODE: u32 = 0o100644;
+
+struct Entry {
+    name: String,
+    size: u64,
+    modified: u64,
+    crc: u32,
+    offset: u64,
+}
+
+impl Entry {
+    fn zip64(&self) -> bool {
+        self.size >= MAX_U32
+    }
+}
+
+pub(super) struct Zip<W> {
+    writer: W,
+    offset: u64,
+    entries: Vec<Entry>,
+}
+
+impl<W: AsyncWrite + Unpin> Zip<W> {
+    pub(super) fn new(writer: W) -> Self {
+        Self {
+            writer,
+            offset: 0,
+            entries: Vec::new(),
+        }
+    }
+
+    pub(super) async fn append<R>(
+        &mut self,
+        name: &str,
+        size: u64,
+        modified: u64,
+        reader: &mut R,
+    ) -> Result<(), IoError>
+    where
+        R: AsyncRead + Unpin,
+    {
+        let mut entry = Entry {
+            name: name.to_owned(),
+            size,
+            modified,
+            crc: 0,
+            offset: self.offset,
+        };
+        self.write(&local_header(&entry)).await?;
+
+        let mut reader = reader.take(size);
+        let mut buffer = vec![0; BUFFER_SIZE];
+        let mut hasher = Hasher::new();
+        let mut copied = 0;
+        loop {
+            let read = reader.read(&mut buffer).await?;
+            if read == 0 {
+                break;
+            }
+            hasher.update(&buffer[..read]);
+            self.write(&buffer[..read]).await?;
+            copied += read as u64;
+        }
+        if copied != size {
+            return Err(IoError::new(
+                ErrorKind::UnexpectedEof,
+                "file shorter than expected",
+            ));
+        }
+
+        entry.crc = hasher.finalize();
+        self.write(&data_descriptor(&entry)).await?;
+        self.entries.push(entry);
+        Ok(())
+    }
+
+    pub(super) async fn finalize(mut self) -> Result<(), IoError> {
+        let central_offset = self.offset;
+        let central = self
+            .entries
+            .iter()
+            .flat_map(central_header)
+            .collect::<Vec<_>>();
+        self.write(&central).await?;
+        self.write(&end_records(
+            self.entries.len() as u64,
+            central_offset,
+            central.len() as u64,
+        ))
+        .await?;
+        self.writer.shutdown().await
+    }
+
+    async fn write(&mut self, data: &[u8]) -> Result<(), IoError> {
+        self.writer.write_all(data).await?;
+        self.offset += data.len() as u64;
+        Ok(())
+    }
+}
+
+// Computed using the same records as the writer, so the Content-Length is always exact.
+#[async_trait]
+impl<W: AsyncWrite + Send + Unpin> Archive for Zip<W> {
+    async fn append_file<R>(
+        &mut self,
+        name: &str,
+        size: u64,
+        modified: u64,
+        reader: &mut R,
+    ) -> Result<(), IoError>
+    where
+        R: AsyncRead + Send + Unpin,
+    {
+        self.append(name, size, modified, reader).await
+    }
+
+    async fn finish(self) -> Result<(), IoError> {
+        self.finalize().await
+    }
+}
+
+pub(super) fn zip_size<'a, I>(files: I) -> u64
+where
+    I: IntoIterator<Item = (&'a str, u64)>,
+{
+    let mut offset = 0;
+    let mut central_size = 0;
+    let mut count = 0;
+    for (name, size) in files {
+        let entry = Entry {
+            name: name.to_owned(),
+            size,
+            modified: 0,
+            crc: 0,
+            offset,
+        };
+        offset += local_header(&entry).len() as u64 + size + data_descriptor(&entry).len() as u64;
+        central_size += central_header(&entry).len() as u64;
+        count += 1;
+    }
+    offset + central_size + end_records(count, offset, central_size).len() as u64
+}
+
+fn local_header(entry: &Entry) -> Vec<u8> {
+    let mut extra = timestamp_extra(entry.modified);
+    if entry.zip64() {
+        // Actual sizes are in the data descriptor.
+        extra.extend(ZIP64_EXTRA_ID.to_le_bytes());
+        extra.extend(16u16.to_le_bytes());
+        extra.extend([0; 16]);
+    }
+    let (time, date) = dos_date_time(entry.modified);
+    let sizes = if entry.zip64() { MAX_U32 as u32 } else { 0 };
+
+    let mut header = Vec::with_capacity(30 + entry.name.len() + extra.len());
+    header.extend(LOCAL_HEADER_SIGNATURE.to_le_bytes());
+    header.extend(version_needed(entry.zip64()).to_le_bytes());
+    header.extend(FLAGS.to_le_bytes());
+    header.extend(0u16.to_le_bytes());
+    header.extend(time.to_le_bytes());
+    header.extend(date.to_le_bytes());
+    header.extend(0u32.to_le_bytes());
+    header.extend(sizes.to_le_bytes());
+    header.extend(sizes.to_le_bytes());
+    header.extend((entry.name.len() as u16).to_le_bytes());
+    header.extend((extra.len() as u16).to_le_bytes());
+    header.extend(entry.name.as_bytes());
+    header.extend(extra);
+    header
+}
+
+fn data_descriptor(entry: &Entry) -> Vec<u8> {
+    let mut descriptor = Vec::with_capacity(24);
+    descriptor.extend(DATA_DESCRIPTOR_SIGNATURE.to_le_bytes());
+    descriptor.extend(entry.crc.to_le_bytes());
+    for _ in 0..2 {
+        if entry.zip64() {
+            descriptor.extend(entry.size.to_le_bytes());
+        } else {
+            descriptor.extend((entry.size as u32).to_le_bytes());
+        }
+    }
+    descriptor
+}
+
+fn central_header(entry: &Entry) -> Vec<u8> {
+    let mut zip64 = Vec::new();
+    if entry.zip64() {
+        zip64.extend(entry.size.to_le_bytes());
+        zip64.extend(entry.size.to_le_bytes());
+    }
+    if entry.offset >= MAX_U32 {
+        zip64.extend(entry.offset.to_le_bytes());
+    }
+    let mut extra = timestamp_extra(entry.modified);
+    if !zip64.is_empty() {
+        extra.extend(ZIP64_EXTRA_ID.to_le_bytes());
+        extra.extend((zip64.len() as u16).to_le_bytes());
+        extra.extend(zip64);
+    }
+    let (time, date) = dos_date_time(entry.modified);
+    let size = entry.size.min(MAX_U32) as u32;
+
+    let mut header = Vec::with_capacity(46 + entry.name.len() + extra.len());
+    header.extend(CENTRAL_HEADER_SIGNATURE.to_le_bytes());
+    header.extend(VERSION_MADE_BY.to_le_bytes());
+    header.extend(version_needed(entry.zip64() || entry.offset >= MAX_U32).to_le_bytes());
+    header.extend(FLAGS.to_le_bytes());
+    header.extend(0u16.to_le_bytes());
+    header.extend(time.to_le_bytes());
+    header.extend(date.to_le_bytes());
+    header.extend(entry.crc.to_le_bytes());
+    header.extend(size.to_le_bytes());
+    header.extend(size.to_le_bytes());
+    header.extend((entry.name.len() as u16).to_le_bytes());
+    header.extend((extra.len() as u16).to_le_bytes());
+    // Comment length, disk number and internal attributes.
+    header.extend([0; 6]);
+    header.extend((FILE_MODE << 16).to_le_bytes());
+    header.extend((entry.offset.min(MAX_U32) as u32).to_le_bytes());
+    header.extend(entry.name.as_bytes());
+    header.extend(extra);
+    header
+}
+
+fn end_records(count: u64, central_offset: u64, central_size: u64) -> Vec<u8> {
+    let mut records = Vec::with_capacity(98);
+    if count >= MAX_U16 || central_offset >= MAX_U32 || central_size >= MAX_U32 {
+        records.extend(ZIP64_END_SIGNATURE.to_le_bytes());
+        // Size of the remaining record.
+        records.extend(44u64.to_le_bytes());
+        records.extend(VERSION_MADE_BY.to_le_bytes());
+        records.extend(VERSION_ZIP64.to_le_bytes());
+        records.extend([0; 8]);
+        records.extend(count.to_le_bytes());
+        records.extend(count.to_le_bytes());
+        records.extend(central_size.to_le_bytes());
+        records.extend(central_offset.to_le_bytes());
+
+        records.extend(ZIP64_LOCATOR_SIGNATURE.to_le_bytes());
+        records.extend(0u32.to_le_bytes());
+        records.extend((central_offset + central_size).to_le_bytes());
+        records.extend(1u32.to_le_bytes());
+    }
+    let count = count.min(MAX_U16) as u16;
+    records.extend(END_SIGNATURE.to_le_bytes());
+    records.extend([0; 4]);
+    records.extend(count.to_le_bytes());
+    records.extend(count.to_le_bytes());
+    records.extend((central_size.min(MAX_U32) as u32).to_le_bytes());
+    records.extend((central_offset.min(MAX_U32) as u32).to_le_bytes());
+    records.extend(0u16.to_le_bytes());
+    records
+}
+
+fn version_needed(zip64: bool) -> u16 {
+    if zip64 {
+        VERSION_ZIP64
+    } else {
+        VERSION_DEFAULT
+    }
+}
+
+// Extended timestamp, as DOS dates have neither a time zone nor a second precision.
+fn timestamp_extra(modified: u64) -> Vec<u8> {
+    let mut extra = Vec::with_capacity(9);
+    extra.extend(TIMESTAMP_EXTRA_ID.to_le_bytes());
+    extra.extend(5u16.to_le_bytes());
+    extra.push(0x01);
+    extra.extend((modified.min(u32::MAX as u64) as u32).to_le_bytes());
+    extra
+}
+
+fn dos_date_time(timestamp: u64) -> (u16, u16) {
+    let (year, month, day) = civil_from_days(timestamp / 86400);
+    if year < 1980 {
+        return (0, (1 << 5) | 1);
+    }
+    if year > 2107 {
+        return ((23 << 11) | (59 << 5) | 29, (127 << 9) | (12 << 5) | 31);
+    }
+    let seconds = timestamp % 86400;
+    let time = (seconds / 3600) << 11 | (seconds % 3600 / 60) << 5 | (seconds % 60 / 2);
+    let date = (year - 1980) << 9 | month << 5 | day;
+    (time as u16, date as u16)
+}
+
+// Howard Hinnant's days to civil algorithm, restricted to dates after the epoch.
+fn civil_from_days(days: u64) -> (u64, u64, u64) {
+    let days = days + 719468;
+    let era = days / 146097;
+    let day_of_era = days % 146097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = year_of_era + era * 400 + (month <= 2) as u64;
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{dos_date_time, zip_size, Zip};
+
+    async fn archive(files: &[(String, Vec<u8>)]) -> Vec<u8> {
+        let mut output = Vec::new();
+        let mut zip = Zip::new(&mut output);
+        for (name, content) in files {
+            zip.append(
+                name,
+                content.len() as u64,
+                1666000000,
+                &mut content.as_slice(),
+            )
+            .await
+            .unwrap();
+        }
+        zip.finalize().await.unwrap();
+        output
+    }
+
+    #[tokio::test]
+    async fn size() {
+        let files = vec![
+            ("empty".to_owned(), Vec::new()),
+            ("small.txt".to_owned(), b"hello".to_vec()),
+            ("dir/sub/file".to_owned(), vec![1; 100_000]),
+        ];
+        let output = archive(&files).await;
+        assert_eq!(
+            output.len() as u64,
+            zip_size(files.iter().map(|(n, c)| (n.as_str(), c.len() as u64)))
+        );
+    }
+
+    #[tokio::test]
+    async fn zip64_entries_count() {
+        let files = (0..70_000)
+            .map(|i| (format!("{}.txt", i), Vec::new()))
+            .collect::<Vec<_>>();
+        let output = archive(&files).await;
+        assert_eq!(
+            output.len() as u64,
+            zip_size(files.iter().map(|(n, c)| (n.as_str(), c.len() as u64)))
+        );
+        // ZIP64 end of central directory locator precedes the regular end record.
+        assert_eq!(output[output.len() - 42..][..4], [0x50, 0x4b, 0x06, 0x07]);
+    }
+
+    #[test]
+    fn zip64_size() {
+        let five_gib = 5 * 1024 * 1024 * 1024;
+        // Local and central ZIP64 extra fields, larger data descriptor and end records.
+        assert_eq!(zip_size([("large", five_gib)]), five_gib + 168 + 98);
+        // Offset of the following entry is moved to its central ZIP64 extra field.
+        assert_eq!(
+            zip_size([("large", five_gib), ("small", 1)]),
+            five_gib + 168 + 133 + 98
+        );
+    }
+
+    #[test]
+    fn date_time() {
+        // 2022-10-17 09:46:40 UTC.
+        assert_eq!(
+            dos_date_time(1666000000),
+            (9 << 11 | 46 << 5 | 20, 42 << 9 | 10 << 5 | 17)
+        );
+        assert_eq!(dos_date_time(0), (0, 1 << 5 | 1));
+    }
+}
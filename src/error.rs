@@ -14,6 +14,10 @@ pub enum Error {
     Generic,
     #[error("invalid filename header")]
     FilenameHeader,
+    #[error("invalid description or tags header")]
+    DetailsHeader,
+    #[error("invalid last modified header")]
+    LastModifiedHeader,
     #[error("file too large")]
     TooLarge,
     #[error("cannot calculate expiration")]
@@ -62,6 +66,26 @@ pub enum Error {
     UnexpectedFileModification,
     #[error("asset not found")]
     AssetNotFound,
+    #[error("cannot determine storage state")]
+    StorageAccess,
+    #[error("cannot find collection")]
+    CollectionNotFound,
+    #[error("collection must contain at least one file")]
+    EmptyCollection,
+    #[error("selected file is not part of the archive")]
+    InvalidSelection,
+    #[error("unsupported oEmbed format")]
+    UnsupportedOEmbedFormat,
+    #[error("cannot generate QR code")]
+    QrCode,
+    #[error("server administrator role required")]
+    AdministratorRequired,
+    #[error("invalid search filters")]
+    InvalidSearch,
+    #[error("too many files in batch request")]
+    BatchTooLarge,
+    #[error("invalid upload group token")]
+    InvalidGroup,
 }
 
 impl Error {
@@ -70,6 +94,8 @@ impl Error {
         match self {
             Generic => StatusCode::INTERNAL_SERVER_ERROR,
             FilenameHeader => StatusCode::BAD_REQUEST,
+            DetailsHeader => StatusCode::BAD_REQUEST,
+            LastModifiedHeader => StatusCode::BAD_REQUEST,
             TooLarge => StatusCode::BAD_REQUEST,
             TimeCalculation => StatusCode::INTERNAL_SERVER_ERROR,
             ExpirationTooHigh => StatusCode::BAD_REQUEST,
@@ -94,6 +120,16 @@ impl Error {
             AccessForbidden => StatusCode::FORBIDDEN,
             UnexpectedFileModification => StatusCode::INTERNAL_SERVER_ERROR,
             AssetNotFound => StatusCode::NOT_FOUND,
+            StorageAccess => StatusCode::INTERNAL_SERVER_ERROR,
+            CollectionNotFound => StatusCode::NOT_FOUND,
+            EmptyCollection => StatusCode::BAD_REQUEST,
+            InvalidSelection => StatusCode::BAD_REQUEST,
+            UnsupportedOEmbedFormat => StatusCode::NOT_IMPLEMENTED,
+            QrCode => StatusCode::INTERNAL_SERVER_ERROR,
+            AdministratorRequired => StatusCode::FORBIDDEN,
+            InvalidSearch => StatusCode::BAD_REQUEST,
+            BatchTooLarge => StatusCode::BAD_REQUEST,
+            InvalidGroup => StatusCode::BAD_REQUEST,
         }
     }
 }
@@ -170,7 +206,8 @@ impl From<hyper::Error> for Error {
 #[allow(unused_imports)]
 pub mod upload {
     pub use super::Error::{
-        AliasGeneration, CopyFile, CreateFile, Database, FilenameHeader, Origin, QuotaAccess,
+        AliasGeneration, CopyFile, CreateFile, Database, DetailsHeader, ExpirationTooHigh,
+        FileNotFound, FilenameHeader, InvalidGroup, LastModifiedHeader, Origin, QuotaAccess,
         QuotaExceeded, SizeMismatch, Target, TimeCalculation, TooLarge,
     };
 }
@@ -178,21 +215,27 @@ pub mod upload {
 #[allow(unused_imports)]
 pub mod download {
     pub use super::Error::{
-        AliasExtract, Database, FileNotFound, FilenameHeader, InvalidAlias, OpenFile,
+        AliasExtract, Database, FileNotFound, FilenameHeader, InvalidAlias, InvalidSelection,
+        OpenFile, QrCode, UnsupportedOEmbedFormat,
     };
 }
 
 #[allow(unused_imports)]
 pub mod admin {
     pub use super::Error::{
-        AliasExtract, Database, FileNotFound, InvalidAdminToken, InvalidAlias,
+        AliasExtract, BatchTooLarge, Database, FileNotFound, InvalidAdminToken, InvalidAlias,
         InvalidAuthorizationHeader,
     };
 }
 
 #[allow(unused_imports)]
 pub mod revoke {
-    pub use super::Error::{PartialRemove, RemoveFile};
+    pub use super::Error::{Database, PartialRemove, RemoveFile, TimeCalculation};
+}
+
+#[allow(unused_imports)]
+pub mod restore {
+    pub use super::Error::{Database, FileNotFound, TimeCalculation, TooLarge};
 }
 
 #[allow(unused_imports)]
@@ -210,16 +253,51 @@ pub mod downloads {
     pub use super::Error::UnexpectedFileModification;
 }
 
+#[allow(unused_imports)]
+pub mod inactivity {
+    pub use super::Error::{ExpirationTooHigh, TimeCalculation, UnexpectedFileModification};
+}
+
+#[allow(unused_imports)]
+pub mod details {
+    pub use super::Error::{DetailsHeader, FilenameHeader, UnexpectedFileModification};
+}
+
 #[allow(unused_imports)]
 pub mod valid {
     pub use super::Error::{AliasExtract, Database, InvalidAlias};
 }
 
+#[allow(unused_imports)]
+pub mod metadata {
+    pub use super::Error::{Database, FileNotFound, InvalidAuthorizationHeader, TimeCalculation};
+}
+
 #[allow(unused_imports)]
 pub mod assets {
     pub use super::Error::AssetNotFound;
 }
 
+#[allow(unused_imports)]
+pub mod health {
+    pub use super::Error::StorageAccess;
+}
+
+#[allow(unused_imports)]
+pub mod collection {
+    pub use super::Error::{
+        AliasGeneration, CollectionNotFound, Database, EmptyCollection, ExpirationTooHigh,
+        FileNotFound, InvalidAdminToken, TooLarge, UnexpectedFileModification,
+    };
+}
+
+#[allow(unused_imports)]
+pub mod administration {
+    pub use super::Error::{
+        AdministratorRequired, Database, InvalidSearch, StorageAccess, TimeCalculation,
+    };
+}
+
 #[allow(unused_imports)]
 pub mod auth {
     pub use super::Error::{AccessForbidden, InvalidAuthorizationHeader, MissingAuthorization};
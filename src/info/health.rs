@@ -0,0 +1,46 @@
+use axum::Extension;
+use http_negotiator::{ContentTypeNegotiation, Negotiation};
+use serde::Serialize;
+
+use crate::{
+    error::{health as HealthError, Error},
+    response::{ApiHeader, ApiResponse, ResponseType, SingleLine},
+    storage::Dir,
+    upload::Size,
+};
+
+#[derive(Serialize)]
+pub struct Health {
+    storage: Storage,
+}
+
+#[derive(Serialize)]
+struct Storage {
+    available: Size,
+}
+
+impl ApiHeader for Health {}
+
+impl SingleLine for Health {
+    fn single_lined(&self) -> String {
+        self.storage.available.readable.clone()
+    }
+}
+
+pub async fn handler(
+    Extension(dir): Extension<Dir>,
+    response_type: Negotiation<ContentTypeNegotiation, ResponseType>,
+) -> Result<ApiResponse<Health>, ApiResponse<Error>> {
+    let available = dir.available_space().await.map_err(|err| {
+        log::error!("Cannot determine uploads directory free space: {}", err);
+        ApiResponse(*response_type, HealthError::StorageAccess)
+    })?;
+    Ok(ApiResponse(
+        *response_type,
+        Health {
+            storage: Storage {
+                available: Size::from(available),
+            },
+        },
+    ))
+}
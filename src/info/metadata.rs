@@ -0,0 +1,193 @@
+use std::{
+    convert::TryFrom,
+    sync::Arc,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use axum::{
+    headers::{authorization::Basic, Authorization, Cookie},
+    Extension, TypedHeader,
+};
+use http_negotiator::{ContentTypeNegotiation, Negotiation};
+use serde::Serialize;
+use sqlx::{FromRow, SqlitePool};
+
+use crate::{
+    alias::Alias,
+    auth::{AuthStatus, Authenticator, Features},
+    error::{auth as AuthError, metadata as MetadataError, Error},
+    include_query,
+    response::{ApiHeader, ApiResponse, ResponseType, SingleLine},
+    update::AdminAccess,
+    upload::{
+        split_tags, Aliases, Determiner, DomainUri, Expiration, ExpirationDuration, Links, Size,
+    },
+};
+
+#[derive(FromRow)]
+pub(crate) struct FileRow {
+    name: String,
+    size: i64,
+    mime: Option<String>,
+    language: Option<String>,
+    downloads: Option<i64>,
+    expiration: i64,
+    uploaded: Option<i64>,
+    short_alias: String,
+    long_alias: String,
+    admin: String,
+    origin: String,
+    description: Option<String>,
+    tags: Option<String>,
+    inactivity: Option<i64>,
+}
+
+#[derive(Serialize)]
+pub struct FileMetadata {
+    name: String,
+    size: Size,
+    alias: Aliases,
+    link: Links,
+    mime: Option<String>,
+    language: Option<String>,
+    downloads: Option<i64>,
+    expiration: Expiration,
+    description: Option<String>,
+    tags: Vec<String>,
+    // Expires after this period without downloads.
+    inactivity: Option<ExpirationDuration>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    admin: Option<AdminMetadata>,
+}
+
+// Only returned to the uploader of the file.
+#[derive(Serialize)]
+struct AdminMetadata {
+    origin: String,
+    uploaded: Option<i64>,
+    // Files larger than the current thresholds cannot be extended anymore.
+    allowed: Option<ExpirationDuration>,
+}
+
+impl FileMetadata {
+    // Admin fields are only filled when given the thresholds determiner.
+    pub(crate) fn new(
+        row: FileRow,
+        domain_uri: &str,
+        determiner: Option<&Determiner>,
+    ) -> Result<Self, Error> {
+        let remaining = (row.expiration.max(0) as u64).saturating_sub(
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_err(|_| MetadataError::TimeCalculation)?
+                .as_secs(),
+        );
+        let admin = determiner.map(|determiner| AdminMetadata {
+            origin: row.origin,
+            uploaded: row.uploaded,
+            allowed: determiner
+                .determine(row.size as u64)
+                .map(|(default, allowed)| ExpirationDuration::from(allowed.unwrap_or(default))),
+        });
+        let aliases = (row.short_alias, row.long_alias);
+        Ok(Self {
+            name: row.name,
+            size: Size::from(row.size as u64),
+            alias: Aliases::new(&aliases),
+            link: Links::new(domain_uri, &aliases),
+            mime: row.mime,
+            language: row.language,
+            downloads: row.downloads,
+            expiration: Expiration::try_from(Duration::from_secs(remaining))?,
+            tags: split_tags(row.tags.as_deref()),
+            description: row.description,
+            inactivity: row
+                .inactivity
+                .map(|period| ExpirationDuration::from(Duration::from_secs(period.max(0) as u64))),
+            admin,
+        })
+    }
+}
+
+impl ApiHeader for FileMetadata {}
+
+impl SingleLine for FileMetadata {
+    fn single_lined(&self) -> String {
+        format!(
+            "{} {} {} {}",
+            self.link.short,
+            self.size.bytes,
+            self.expiration.timestamp(),
+            self.name
+        )
+    }
+}
+
+// Reading metadata never consumes one of the file's downloads.
+#[allow(clippy::too_many_arguments)]
+pub async fn handler(
+    Extension(pool): Extension<SqlitePool>,
+    response_type: Negotiation<ContentTypeNegotiation, ResponseType>,
+    authenticator: Extension<Arc<Authenticator>>,
+    auth_header: Option<TypedHeader<Authorization<Basic>>>,
+    cookie: Option<TypedHeader<Cookie>>,
+    access: Option<AdminAccess>,
+    alias: Alias,
+    DomainUri(domain_uri): DomainUri,
+    Extension(determiner): Extension<Arc<Determiner>>,
+) -> Result<ApiResponse<FileMetadata>, ApiResponse<Error>> {
+    process_metadata(
+        pool,
+        &authenticator,
+        auth_header,
+        cookie,
+        access,
+        alias,
+        domain_uri,
+        &determiner,
+    )
+    .await
+    .map(|metadata| ApiResponse(*response_type, metadata))
+    .map_err(|err| ApiResponse(*response_type, err))
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn process_metadata(
+    pool: SqlitePool,
+    authenticator: &Authenticator,
+    auth_header: Option<TypedHeader<Authorization<Basic>>>,
+    cookie: Option<TypedHeader<Cookie>>,
+    access: Option<AdminAccess>,
+    alias: Alias,
+    domain_uri: String,
+    determiner: &Determiner,
+) -> Result<FileMetadata, Error> {
+    let row = sqlx::query_as::<_, FileRow>(include_query!("get_file_metadata"))
+        .bind(alias.inner())
+        .bind(alias.inner())
+        .fetch_optional(&pool)
+        .await
+        .map_err(|_| MetadataError::Database)?;
+
+    // Owners may read their files' metadata even if downloads require authentication.
+    let is_admin = match (&row, access) {
+        (Some(row), Some(access)) => access.check(&row.admin, &row.origin).is_ok(),
+        _ => false,
+    };
+    if !is_admin {
+        match authenticator
+            .allows(
+                auth_header.map(|h| h.0),
+                cookie.map(|h| h.0),
+                Features::DOWNLOAD,
+            )
+            .await
+        {
+            AuthStatus::NotNeeded | AuthStatus::Valid(_) => (),
+            AuthStatus::Error(err) => return Err(err),
+            AuthStatus::Prompt => return Err(AuthError::MissingAuthorization),
+        }
+    }
+    let row = row.ok_or(MetadataError::FileNotFound)?;
+    FileMetadata::new(row, &domain_uri, Some(determiner).filter(|_| is_admin))
+}
@@ -1,10 +1,36 @@
+use std::sync::Arc;
+
 use axum::{routing::get, Extension, Router};
 use sqlx::SqlitePool;
 
+use crate::{
+    auth::{Authenticator, Origin},
+    storage::Dir,
+    upload::Determiner,
+};
+
+pub(crate) use metadata::{FileMetadata, FileRow};
+
+mod health;
+mod metadata;
+mod uploads;
 mod valid;
 
-pub fn router(pool: SqlitePool) -> Router {
+pub fn router(
+    pool: SqlitePool,
+    dir: Dir,
+    authenticator: Arc<Authenticator>,
+    determiner: Arc<Determiner>,
+    origin: Origin,
+) -> Router {
     Router::new()
         .route("/valid/:alias", get(valid::handler))
+        .route("/:alias/info", get(metadata::handler))
+        .route("/uploads/files", get(uploads::handler))
+        .route("/health", get(health::handler))
         .route_layer(Extension(pool))
+        .route_layer(Extension(dir))
+        .route_layer(Extension(authenticator))
+        .route_layer(Extension(determiner))
+        .route_layer(Extension(origin))
 }
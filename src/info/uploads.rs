@@ -0,0 +1,76 @@
+use std::sync::Arc;
+
+use axum::{extract::Query, Extension};
+use http_negotiator::{ContentTypeNegotiation, Negotiation};
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+
+use crate::{
+    error::{metadata as MetadataError, Error},
+    include_query,
+    info::{FileMetadata, FileRow},
+    response::{ApiHeader, ApiResponse, ResponseType, SingleLine},
+    update::AdminAccess,
+    upload::{Determiner, DomainUri},
+};
+
+#[derive(Deserialize)]
+pub struct UploadsQuery {
+    // Lists files waiting in the trash instead of available ones.
+    #[serde(default)]
+    trashed: bool,
+}
+
+#[derive(Serialize)]
+pub struct Uploads {
+    files: Vec<FileMetadata>,
+}
+
+impl ApiHeader for Uploads {}
+
+impl SingleLine for Uploads {
+    fn single_lined(&self) -> String {
+        self.files.iter().map(|file| file.single_lined()).join("\n")
+    }
+}
+
+// Lists every file of the authenticated uploader, which requires usernames as origins.
+pub async fn handler(
+    Extension(pool): Extension<SqlitePool>,
+    response_type: Negotiation<ContentTypeNegotiation, ResponseType>,
+    access: AdminAccess,
+    DomainUri(domain_uri): DomainUri,
+    Extension(determiner): Extension<Arc<Determiner>>,
+    Query(query): Query<UploadsQuery>,
+) -> Result<ApiResponse<Uploads>, ApiResponse<Error>> {
+    process_uploads(pool, access, query, domain_uri, &determiner)
+        .await
+        .map(|uploads| ApiResponse(*response_type, uploads))
+        .map_err(|err| ApiResponse(*response_type, err))
+}
+
+async fn process_uploads(
+    pool: SqlitePool,
+    access: AdminAccess,
+    query: UploadsQuery,
+    domain_uri: String,
+    determiner: &Determiner,
+) -> Result<Uploads, Error> {
+    let username = match access {
+        AdminAccess::Owner(username) | AdminAccess::Administrator(username) => username,
+        AdminAccess::Token(_) => return Err(MetadataError::InvalidAuthorizationHeader),
+    };
+    let rows = sqlx::query_as::<_, FileRow>(include_query!("get_files_origin"))
+        .bind(&username)
+        .bind(query.trashed)
+        .fetch_all(&pool)
+        .await
+        .map_err(|_| MetadataError::Database)?;
+    Ok(Uploads {
+        files: rows
+            .into_iter()
+            .map(|row| FileMetadata::new(row, &domain_uri, Some(determiner)))
+            .collect::<Result<_, _>>()?,
+    })
+}
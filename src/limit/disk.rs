@@ -0,0 +1,35 @@
+use async_trait::async_trait;
+use sqlx::SqliteConnection;
+
+use crate::{include_query, limit::Limiter, storage::Dir, upload::UploadRequest};
+
+pub struct Disk {
+    dir: Dir,
+    reserve: u64,
+}
+
+impl Disk {
+    pub fn new(dir: Dir, reserve: u64) -> Self {
+        Self { dir, reserve }
+    }
+}
+
+#[async_trait]
+impl Limiter for Disk {
+    async fn accept(&self, req: &UploadRequest, conn: &mut SqliteConnection) -> Option<bool> {
+        let available = match self.dir.available_space().await {
+            Ok(available) => available,
+            Err(err) => {
+                log::error!("Cannot determine uploads directory free space: {}", err);
+                return None;
+            }
+        };
+        // Uploads in progress haven't written their whole body yet, so consider them as already stored.
+        // Replaced contents are only removed once the new one is written, so they are not deducted.
+        let (pending,) = sqlx::query_as::<_, (i64,)>(include_query!("get_limit_pending"))
+            .fetch_one(conn)
+            .await
+            .ok()?;
+        Some(pending as u64 + req.size + self.reserve <= available)
+    }
+}
@@ -20,6 +20,6 @@ impl Limiter for Global {
             .fetch_one(conn)
             .await
             .ok()?;
-        Some(size as u64 + req.size <= self.size_sum)
+        Some((size as u64).saturating_sub(req.replaced.unwrap_or(0)) + req.size <= self.size_sum)
     }
 }
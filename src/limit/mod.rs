@@ -3,9 +3,11 @@ use sqlx::SqliteConnection;
 
 use crate::upload::UploadRequest;
 
+mod disk;
 mod global;
 mod origin;
 
+pub use disk::Disk;
 pub use global::Global;
 pub use origin::Origin;
 
@@ -19,13 +19,20 @@ impl Origin {
 
 #[async_trait]
 impl Limiter for Origin {
-    #[allow(clippy::int_plus_one)]
     async fn accept(&self, req: &UploadRequest, conn: &mut SqliteConnection) -> Option<bool> {
         let (size, count) = sqlx::query_as::<_, (i64, i64)>(include_query!("get_limit_origin"))
             .bind(&req.origin)
             .fetch_one(conn)
             .await
             .ok()?;
-        Some(size as u64 + req.size <= self.size_sum && count as usize + 1 <= self.file_count)
+        // Replacing a file's content doesn't add a file, and frees its previous size.
+        let (replaced_size, added) = match req.replaced {
+            Some(replaced_size) => (replaced_size, 0),
+            None => (0, 1),
+        };
+        Some(
+            (size as u64).saturating_sub(replaced_size) + req.size <= self.size_sum
+                && count as usize + added <= self.file_count,
+        )
     }
 }
@@ -1,12 +1,15 @@
+mod admin;
 mod alias;
 mod assets;
 mod auth;
+mod collection;
 mod download;
 mod error;
 mod info;
 mod limit;
 mod misc;
 mod options;
+mod qr;
 mod query;
 mod response;
 mod storage;
@@ -24,12 +27,17 @@ mod main {
     use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
 
     use crate::{
+        admin::Quotas,
         auth::Authenticator,
-        exit_error, include_query,
-        limit::{Chain as LimiterChain, Global as GlobalLimiter, Origin as OriginLimiter},
+        download::OpenGraph,
+        exit_error,
+        limit::{
+            Chain as LimiterChain, Disk as DiskLimiter, Global as GlobalLimiter,
+            Origin as OriginLimiter,
+        },
         options::Options,
         response::ResponseType,
-        storage::{Cleaner, Dir},
+        storage::{Cleaner, Dir, Reconciler, Scheduler},
         upload::{Determiner, RealIp},
     };
 
@@ -39,13 +47,6 @@ mod main {
             .filter_level(options.log_level())
             .init();
 
-        let limiters = LimiterChain::new(vec![
-            Box::new(OriginLimiter::new(
-                options.origin_size_sum,
-                options.origin_file_count,
-            )),
-            Box::new(GlobalLimiter::new(options.global_size_sum)),
-        ]);
         let determiner = Arc::new(
             Determiner::new(options.thresholds.clone())
                 .unwrap_or_else(|err| exit_error!("Invalid thresholds: {}", err)),
@@ -61,25 +62,74 @@ mod main {
             )
             .await
             .unwrap_or_else(|err| exit_error!("Cannot create database pool: {}", err));
-        sqlx::query(include_query!("migration"))
-            .execute(&pool)
+        super::query::migrate(&pool)
             .await
-            .unwrap_or_else(|err| exit_error!("Cannot run migration query: {}", err));
+            .unwrap_or_else(|err| exit_error!("Cannot run migration queries: {}", err));
 
-        let dir = Dir::new(options.uploads_dir.clone());
+        let dir = Dir::new(options.uploads_dir.clone(), options.shard_depth as usize);
         dir.create(!options.no_uploads_dir_creation)
             .await
             .unwrap_or_else(|err| exit_error!("{}", err));
+        let limiters = LimiterChain::new(vec![
+            Box::new(OriginLimiter::new(
+                options.origin_size_sum,
+                options.origin_file_count,
+            )),
+            Box::new(GlobalLimiter::new(options.global_size_sum)),
+            Box::new(DiskLimiter::new(dir.clone(), options.disk_reserve)),
+        ]);
 
-        let cleaner = Cleaner::new(dir.clone(), pool.clone());
+        let scheduler = Arc::new(Scheduler::new());
+        let cleaner = Arc::new(Cleaner::new(
+            dir.clone(),
+            pool.clone(),
+            options.trash_grace,
+            Arc::clone(&scheduler),
+        ));
+        cleaner.clean_pending().await;
+        let expiration_cleaner = Arc::clone(&cleaner);
+        tokio::task::spawn(async move {
+            expiration_cleaner.start().await;
+        });
+        let purge_cleaner = Arc::clone(&cleaner);
         tokio::task::spawn(async move {
-            cleaner.start().await;
+            purge_cleaner.start_purge().await;
+        });
+
+        let reconciler = Reconciler::new(dir.clone(), pool.clone(), options.reconciliation_policy);
+        let reconciliation_interval = options.reconciliation_interval;
+        let layout_dir = dir.clone();
+        // Files moved during a pass would be reported as missing, so reconciliation waits for the migration.
+        tokio::task::spawn(async move {
+            match layout_dir.migrate_layout().await {
+                Ok(0) => (),
+                Ok(moved) => log::info!("Moved {} file(s) to the current storage layout", moved),
+                Err(err) => log::error!(
+                    "Cannot migrate files to the current storage layout: {}",
+                    err
+                ),
+            }
+            reconciler.reconcile().await;
+            reconciler.start(reconciliation_interval).await;
         });
 
         let authenticator = Arc::new(Authenticator::new(
             options.access(),
             options.credentials.clone(),
             options.ldap_authenticator(),
+            options.admins.clone(),
+        ));
+
+        let origin = options
+            .origin()
+            .unwrap_or_else(|| exit_error!("Invalid origin method"));
+        let open_graph = Arc::new(OpenGraph::new(
+            &options.preview_bots,
+            options.preview_template.as_ref().map(|path| {
+                std::fs::read_to_string(path).unwrap_or_else(|err| {
+                    exit_error!("Cannot read preview template {}: {}", path.display(), err)
+                })
+            }),
         ));
 
         let router = Router::new()
@@ -90,24 +140,51 @@ mod main {
                 pool.clone(),
                 Arc::clone(&authenticator),
                 RealIp::new(options.behind_proxy),
-                options
-                    .origin()
-                    .unwrap_or_else(|| exit_error!("Invalid origin method")),
+                origin,
                 limiters,
                 Arc::clone(&determiner),
                 dir.clone(),
+                options.compression(),
+                Arc::clone(&scheduler),
             ))
             .merge(super::download::router(
                 pool.clone(),
                 Arc::clone(&authenticator),
                 dir.clone(),
+                Arc::clone(&cleaner),
+                open_graph,
             ))
             .merge(super::update::router(
+                pool.clone(),
+                Arc::clone(&cleaner),
+                Arc::clone(&scheduler),
+                Arc::clone(&determiner),
+                Arc::clone(&authenticator),
+                origin,
+            ))
+            .merge(super::collection::router(
+                pool.clone(),
+                Arc::clone(&determiner),
+                Arc::clone(&scheduler),
+            ))
+            .merge(super::info::router(
+                pool.clone(),
+                dir.clone(),
+                Arc::clone(&authenticator),
+                Arc::clone(&determiner),
+                origin,
+            ))
+            .merge(super::admin::router(
                 pool.clone(),
                 dir.clone(),
+                Arc::clone(&authenticator),
                 Arc::clone(&determiner),
+                Arc::new(Quotas {
+                    origin_size_sum: options.origin_size_sum,
+                    origin_file_count: options.origin_file_count,
+                    global_size_sum: options.global_size_sum,
+                }),
             ))
-            .merge(super::info::router(pool.clone()))
             .route_layer(
                 Negotiator::<ContentTypeNegotiation, _>::new([
                     ResponseType::Json,
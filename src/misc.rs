@@ -53,3 +53,65 @@ pub fn format_duration(duration: Duration) -> String {
         )
     }
 }
+
+pub fn escape_html(input: &str) -> String {
+    let mut escaped = String::with_capacity(input.len());
+    for c in input.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+// Placeholders ($TITLE) are replaced in a single pass over the template, so inserted values
+// are never searched for placeholders themselves.
+pub fn fill_template(template: &str, values: &[(&str, &str)]) -> String {
+    let mut page = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(index) = rest.find('$') {
+        page.push_str(&rest[..index]);
+        rest = &rest[index..];
+        match values
+            .iter()
+            .filter(|(placeholder, _)| rest.starts_with(placeholder))
+            .max_by_key(|(placeholder, _)| placeholder.len())
+        {
+            Some((placeholder, value)) => {
+                page.push_str(value);
+                rest = &rest[placeholder.len()..];
+            }
+            None => {
+                page.push('$');
+                rest = &rest[1..];
+            }
+        }
+    }
+    page.push_str(rest);
+    page
+}
+
+#[cfg(test)]
+mod tests {
+    use super::fill_template;
+
+    #[test]
+    fn template() {
+        assert_eq!(
+            fill_template(
+                "<h1>$TITLE</h1><p>$DESCRIPTION</p><a href=\"$URL\">$TITLE</a> $5",
+                &[
+                    ("$TITLE", "$URL"),
+                    ("$DESCRIPTION", "costs $DESCRIPTION"),
+                    ("$URL", "https://example.com"),
+                ]
+            ),
+            "<h1>$URL</h1><p>costs $DESCRIPTION</p><a href=\"https://example.com\">$URL</a> $5"
+        );
+    }
+}
@@ -1,4 +1,4 @@
-use std::{net::IpAddr, path::PathBuf};
+use std::{net::IpAddr, path::PathBuf, time::Duration};
 
 use byte_unit::{Byte, ByteError};
 use clap::{ArgAction, ArgGroup, Parser};
@@ -6,9 +6,22 @@ use log::LevelFilter;
 
 use crate::{
     auth::{Credential, Features, LdapAuthProcess, LdapAuthenticator, Origin},
+    storage::{Compression, ReconciliationPolicy},
     upload::Threshold,
 };
 
+const PREVIEW_BOTS: [&str; 9] = [
+    "discord",
+    "facebook",
+    "twitter",
+    "slack",
+    "mattermost",
+    "skypeuripreview",
+    "telegram",
+    "whatsapp",
+    "linkedin",
+];
+
 #[derive(Parser, Debug)]
 #[command(version, about)]
 #[command(
@@ -26,6 +39,9 @@ pub struct Options {
     /// Disable upload files directory automatic creation (if missing).
     #[arg(short = 'U', long)]
     pub no_uploads_dir_creation: bool,
+    /// Number of sub-directory levels used to store upload files (0 for a flat directory).
+    #[arg(long, default_value = "0", value_parser = clap::value_parser!(u8).range(0..=4))]
+    pub shard_depth: u8,
     /// Metadata database path (relative).
     #[arg(short = 'd', long, default_value = "dropit.db")]
     pub database: PathBuf,
@@ -59,6 +75,12 @@ pub struct Options {
     /// Cumulative size limit from all users.
     #[arg(short = 'S', long, required = true, value_parser(parse_size))]
     pub global_size_sum: u64,
+    /// Compress compressible uploads at rest using zstd.
+    #[arg(long)]
+    pub compress: bool,
+    /// Free space to keep available on the uploads directory volume.
+    #[arg(long, default_value = "0B", value_parser(parse_size))]
+    pub disk_reserve: u64,
     /// Protect upload endpoint with authentication.
     #[arg(long, requires = "auth")]
     pub auth_upload: bool,
@@ -86,9 +108,30 @@ pub struct Options {
     /// LDAP password used to bind during username searches.
     #[arg(long, requires = "ldap_search_dn")]
     pub ldap_search_password: Option<String>,
+    /// LDAP DN of the group whose members are server administrators.
+    #[arg(long, requires = "ldap_address")]
+    pub ldap_admin_group: Option<String>,
+    /// Username granted the server administrator role.
+    #[arg(long = "admin", requires = "auth")]
+    pub admins: Vec<String>,
     /// CSS color used in the web UI.
     #[arg(short = 'T', long, default_value = "#15b154")]
     pub theme: String,
+    /// Action taken on inconsistencies between the database and the uploads directory (report, repair).
+    #[arg(long, default_value = "report")]
+    pub reconciliation_policy: ReconciliationPolicy,
+    /// Interval between storage reconciliations, in addition to the one at startup (0 to disable).
+    #[arg(long, default_value = "1h", value_parser(parse_duration))]
+    pub reconciliation_interval: Duration,
+    /// Time revoked, expired and fully downloaded files stay restorable before their deletion (0 to delete them at once).
+    #[arg(long, default_value = "0s", value_parser(parse_duration))]
+    pub trash_grace: Duration,
+    /// User agent patterns (case insensitive) of link preview bots receiving an Open Graph page instead of files.
+    #[arg(long = "preview-bot", default_values = PREVIEW_BOTS)]
+    pub preview_bots: Vec<String>,
+    /// HTML template of the page sent to link preview bots, instead of the embedded one.
+    #[arg(long)]
+    pub preview_template: Option<PathBuf>,
 }
 
 impl Options {
@@ -113,6 +156,14 @@ impl Options {
         }
     }
 
+    pub fn compression(&self) -> Option<Compression> {
+        if self.compress {
+            Some(Compression::Zstd)
+        } else {
+            None
+        }
+    }
+
     pub fn access(&self) -> Features {
         let mut access = Features::empty();
         if self.auth_upload {
@@ -143,6 +194,7 @@ impl Options {
         Some(LdapAuthenticator::new(
             self.ldap_address.as_ref()?.clone(),
             process,
+            self.ldap_admin_group.clone(),
         ))
     }
 }
@@ -151,6 +203,10 @@ fn parse_size(s: &str) -> Result<u64, ByteError> {
     Ok(s.parse::<Byte>()?.get_bytes())
 }
 
+fn parse_duration(s: &str) -> Result<Duration, humantime::DurationError> {
+    Ok(s.parse::<humantime::Duration>()?.into())
+}
+
 #[cfg(test)]
 mod tests {
     use clap::{
@@ -305,6 +361,22 @@ mod tests {
             "org=MyOrg,uid=%u"
         ]
         .is_ok());
+
+        // Administrators without auth method.
+        missing_args(
+            cmd!["--ip-origin", "--admin", "root"].unwrap_err(),
+            ["credential", "ldap-address"],
+        );
+
+        // Administrators with static credentials.
+        assert!(cmd![
+            "--ip-origin",
+            "--credential",
+            "root:password",
+            "--admin",
+            "root",
+        ]
+        .is_ok());
     }
 
     #[test]
@@ -0,0 +1,153 @@
+function request(method, path, onSuccess) {
+    const req = new XMLHttpRequest();
+    req.open(method, path, true);
+    req.setRequestHeader('Accept', 'application/json');
+    req.responseType = 'json';
+    req.onload = () => {
+        if (req.status === 401) {
+            window.location = '/login/';
+        } else if (req.status >= 200 && req.status < 300) {
+            onSuccess(req.response);
+        } else {
+            alert(`An error occurred: ${req.response.error}.`);
+            console.error(`An error occurred: ${req.response.error}.`);
+        }
+    };
+    req.send();
+}
+
+function loadStats() {
+    request('GET', '/admin/stats', (stats) => {
+        document.querySelector('.stats').innerText = [
+            `${stats.files} ${'file'.plural(stats.files)}`,
+            `${stats.pending} pending ${'upload'.plural(stats.pending)}`,
+            `${stats.trashed} trashed ${'file'.plural(stats.trashed)}`,
+            `${stats.collections} ${'collection'.plural(stats.collections)}`,
+            `${stats.size.readable} used of ${stats.limit.readable}`,
+            `${stats.available.readable} available on disk`,
+        ].join(', ');
+    });
+}
+
+function loadFiles() {
+    const params = new URLSearchParams(
+        [...new FormData(document.querySelector('.search'))].filter(([_, value]) => value !== ''),
+    );
+    request('GET', `/admin/files?${params}`, (response) => {
+        const table = document.querySelector('.files');
+        table.innerHTML = '';
+        for (const file of response.files) {
+            table.append(fileRow(file, params.has('trashed')));
+        }
+    });
+}
+
+function loadOrigins() {
+    request('GET', '/admin/origins', (response) => {
+        const table = document.querySelector('.origins');
+        table.innerHTML = '';
+        for (const usage of response.origins) {
+            const row = document.createElement('tr');
+            if (usage.size.bytes > response.limit.size.bytes || usage.files > response.limit.files) {
+                row.classList.add('over');
+            }
+            row.append(
+                cell(usage.origin, 'name'),
+                cell(`${usage.size.readable} / ${response.limit.size.readable}`, 'secondary'),
+                cell(`${usage.files} / ${response.limit.files} ${'file'.plural(response.limit.files)}`, 'secondary'),
+            );
+            table.append(row);
+        }
+    });
+}
+
+function loadSessions() {
+    request('GET', '/admin/sessions', (response) => {
+        const table = document.querySelector('.sessions');
+        table.innerHTML = '';
+        for (const user of response.users) {
+            const row = document.createElement('tr');
+            row.append(
+                cell(user.administrator ? `${user.username} (administrator)` : user.username, 'name'),
+                cell(`${user.sessions} ${'session'.plural(user.sessions)}`, 'secondary'),
+            );
+            table.append(row);
+        }
+    });
+}
+
+function load() {
+    loadStats();
+    loadFiles();
+    loadOrigins();
+}
+
+function fileRow(file, trashed) {
+    const row = document.createElement('tr');
+
+    const name = document.createElement('td');
+    name.classList.add('name');
+    const link = document.createElement('a');
+    link.href = file.link.short;
+    link.innerText = file.name;
+    name.append(link);
+
+    const operations = document.createElement('td');
+    operations.classList.add('operations');
+    // Trashed files can only be restored, they are deleted once their grace period ends.
+    if (trashed) {
+        operations.append(operation('Restore', () => {
+            request('PATCH', `/${file.alias.short}/restore`, load);
+        }));
+    } else {
+        operations.append(
+            operation('Extend', () => {
+                const hours = prompt('New duration from now, in hours:', '24');
+                if (hours !== null && /^\d+$/.test(hours)) {
+                    request('PATCH', `/${file.alias.short}/expiration/${hours * 3600}`, load);
+                }
+            }),
+            operation('Delete', () => {
+                if (confirm(`Delete ${file.name} uploaded by ${file.admin.origin}?`)) {
+                    request('DELETE', `/${file.alias.short}`, load);
+                }
+            }, 'revoke'),
+        );
+    }
+
+    row.append(
+        name,
+        cell(file.admin.origin, 'secondary'),
+        cell(file.size.readable, 'secondary'),
+        cell(trashed ? 'Trashed' : `Expires in ${file.expiration.duration.readable}`, 'secondary'),
+        operations,
+    );
+    return row;
+}
+
+function cell(text, ...classes) {
+    const cell = document.createElement('td');
+    cell.classList.add(...classes);
+    cell.innerText = text;
+    return cell;
+}
+
+function operation(label, onClick, ...classes) {
+    const operation = document.createElement('div');
+    operation.classList.add('clickable', ...classes);
+    operation.innerText = label;
+    operation.addEventListener('click', onClick);
+    return operation;
+}
+
+String.prototype.plural = function (n) {
+    return n >= 2 ? `${this}s` : this;
+};
+
+document.querySelector('.search').addEventListener('submit', (event) => {
+    event.preventDefault();
+    loadFiles();
+});
+
+load();
+loadSessions();
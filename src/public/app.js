@@ -139,8 +139,9 @@ function ready() {
     }
 
     class File {
-        constructor(fileRef) {
+        constructor(fileRef, path) {
             this.fileRef = fileRef;
+            this.path = path || (fileRef && fileRef.name);
         }
 
         buildBase(filename) {
@@ -164,10 +165,24 @@ function ready() {
 
             const req = new XMLHttpRequest();
             req.open('POST', '/', true);
-            req.setRequestHeader('X-Filename', encodeURIComponent(this.fileRef.name));
-            req.setRequestHeader('Content-Type', this.fileRef.type);
+            if (this.path) {
+                req.setRequestHeader('X-Filename', encodeURIComponent(this.path));
+            }
+            // Only text typed in the paste editor is uploaded as a paste, dropped text files stay files.
+            req.setRequestHeader('Content-Type', this.paste ? 'text/plain' : 'application/octet-stream');
+            if (this.language) {
+                req.setRequestHeader('X-Language', this.language);
+            }
+            if (this.fileRef.lastModified) {
+                req.setRequestHeader('X-Last-Modified', this.fileRef.lastModified);
+            }
             req.responseType = 'json';
 
+            let resolveUpload;
+            const uploaded = new Promise((resolve) => {
+                resolveUpload = resolve;
+            });
+
             this.progress = 0;
             let showingProgress = false;
             const showProgressTimeout = setTimeout(() => {
@@ -194,6 +209,7 @@ function ready() {
                         FILES.updateButtons();
                         FILES.save();
                     }, showingProgress ? 550 : 0);
+                    resolveUpload(resp);
                 } else {
                     this.node.classList.add('error');
                     this.progressBar.style.backgroundColor = '#ff5d24';
@@ -203,9 +219,11 @@ function ready() {
                         this.buildError(req.response);
                         FILES.save();
                     }, showingProgress ? 550 : 0);
+                    resolveUpload(null);
                 }
             };
             req.send(this.fileRef);
+            return uploaded;
         }
 
         updateProgressBar() {
@@ -216,6 +234,8 @@ function ready() {
         buildDetails(data) {
             this.state = 'available';
             this.info = data;
+            // Collections are administrated using their own routes.
+            const adminBase = this.info.collection ? '/collection' : '';
 
             const link = document.createElement('div');
             link.classList.add('link', 'selectable');
@@ -332,6 +352,13 @@ function ready() {
                 document.location = this.info.link.short;
             });
 
+            const listing = document.createElement('div');
+            listing.classList.add('item');
+            listing.innerText = 'Show files';
+            listing.addEventListener('click', () => {
+                window.open(`/collection/${this.info.alias.short}`);
+            });
+
             const separator = document.createElement('div');
             separator.classList.add('separator');
 
@@ -420,7 +447,7 @@ function ready() {
                 }
                 if (confirm(confirmMessage)) {
                     const req = new XMLHttpRequest();
-                    req.open('PATCH', `/${this.info.alias.short}/expiration/${duration}`, true);
+                    req.open('PATCH', `${adminBase}/${this.info.alias.short}/expiration/${duration}`, true);
                     req.setRequestHeader('Authorization', this.info.admin);
                     req.setRequestHeader('X-Authorization', this.info.admin);
                     req.responseType = 'json';
@@ -518,7 +545,7 @@ function ready() {
                 count.innerText = n ? `${n} ${'download'.plural(n)}` : 'Unlimited';
                 count.addEventListener('click', () => {
                     const req = new XMLHttpRequest();
-                    req.open('PATCH', `/${this.info.alias.short}/downloads/${n}`, true);
+                    req.open('PATCH', `${adminBase}/${this.info.alias.short}/downloads/${n}`, true);
                     req.setRequestHeader('Authorization', this.info.admin);
                     req.setRequestHeader('X-Authorization', this.info.admin);
                     req.responseType = 'json';
@@ -548,7 +575,7 @@ function ready() {
             revoke.addEventListener('click', () => {
                 if (confirm('Revoking this file will make all people with a link unable to access it. Confirm?')) {
                     const req = new XMLHttpRequest();
-                    req.open('DELETE', `/${this.info.alias.short}`, true);
+                    req.open('DELETE', `${adminBase}/${this.info.alias.short}`, true);
                     req.setRequestHeader('Authorization', this.info.admin);
                     req.setRequestHeader('X-Authorization', this.info.admin);
                     req.responseType = 'json';
@@ -574,7 +601,11 @@ function ready() {
             newAlias.append(aliasMenu);
             extend.append(extendMenu);
             downloads.append(downloadsMenu);
-            menu.append(download, separator.cloneNode(), copyLong, newAlias, separator, extend, downloads, separator.cloneNode(), forget, revoke);
+            if (this.info.collection) {
+                menu.append(download, listing, separator.cloneNode(), copyLong, separator, extend, downloads, separator.cloneNode(), forget, revoke);
+            } else {
+                menu.append(download, separator.cloneNode(), copyLong, newAlias, separator, extend, downloads, separator.cloneNode(), forget, revoke);
+            }
 
             if (this.progressBar) this.progressBar.remove();
             if (this.percent) this.percent.remove();
@@ -628,6 +659,72 @@ function ready() {
         }
     }
 
+    // Files of a dropped directory keep their relative path and are grouped into a collection.
+    function uploadDirectory(name, entries) {
+        const uploads = entries.map(({ file, path }) => {
+            const upload = new File(file, path);
+            upload.buildBase(path);
+            const uploaded = upload.startUpload();
+            FILES.add(upload);
+            return uploaded;
+        });
+        Promise.all(uploads).then((infos) => {
+            const members = infos.filter(info => info !== null);
+            if (members.length > 0) {
+                createCollection(name, members);
+            }
+        });
+    }
+
+    function createCollection(name, members) {
+        const req = new XMLHttpRequest();
+        req.open('POST', '/collection', true);
+        req.setRequestHeader('Content-Type', 'application/json');
+        req.responseType = 'json';
+        req.onload = () => {
+            if (req.status === 201) {
+                const resp = req.response;
+                delete resp.success;
+                resp.collection = true;
+
+                const collection = new File(null);
+                collection.buildBase(resp.name);
+                collection.buildDetails(resp);
+                FILES.add(collection);
+            } else {
+                alert(`An error occurred while grouping the directory's files: ${req.response.error}.`);
+            }
+        };
+        req.send(JSON.stringify({
+            name: name,
+            files: members.map(info => ({ alias: info.alias.short, admin: info.admin })),
+        }));
+    }
+
+    function readEntries(reader) {
+        return new Promise((resolve, reject) => {
+            reader.readEntries(resolve, reject);
+        });
+    }
+
+    async function collectDirectory(entry, files) {
+        if (entry.isFile) {
+            const file = await new Promise((resolve, reject) => {
+                entry.file(resolve, reject);
+            });
+            files.push({ file, path: entry.fullPath.replace(/^\//, '') });
+        } else if (entry.isDirectory) {
+            const reader = entry.createReader();
+            // Entries are returned by batches, until an empty one.
+            for (let batch = await readEntries(reader); batch.length > 0; batch = await readEntries(reader)) {
+                for (const child of batch) {
+                    await collectDirectory(child, files);
+                }
+            }
+        }
+        return files;
+    }
+
     // Form input.
     document.querySelectorAll('input[type=file]').forEach((elem) => {
         elem.addEventListener('change', (event) => {
@@ -635,6 +732,28 @@ function ready() {
         });
     });
 
+    // Text pastes.
+    const pasteEditor = document.querySelector('.paste-editor');
+    document.querySelector('.paste-text').addEventListener('click', () => {
+        document.body.classList.toggle('pasting');
+        pasteEditor.querySelector('textarea').focus();
+    });
+    pasteEditor.querySelector('.submit').addEventListener('click', () => {
+        const content = pasteEditor.querySelector('textarea');
+        const language = pasteEditor.querySelector('.language');
+        if (content.value.length === 0) return;
+
+        const file = new File(new Blob([content.value], { type: 'text/plain' }));
+        file.paste = true;
+        file.language = language.value.trim();
+        file.buildBase('Paste');
+        file.startUpload();
+        FILES.add(file);
+
+        content.value = '';
+        document.body.classList.remove('pasting');
+    });
+
     // Drag and drop.
     ['drag', 'dragstart', 'dragend', 'dragover', 'dragenter', 'dragleave', 'drop'].forEach((name) => {
         document.body.addEventListener(name, event => {
@@ -654,12 +773,22 @@ function ready() {
     });
     document.body.addEventListener('drop', (event) => {
         const files = [];
+        const directories = [];
         for (let item of event.dataTransfer.items) {
             if (item.kind !== 'file') continue;
-            if (item.webkitGetAsEntry && item.webkitGetAsEntry().isDirectory) continue;
+            const entry = item.webkitGetAsEntry && item.webkitGetAsEntry();
+            if (entry && entry.isDirectory) {
+                directories.push(entry);
+                continue;
+            }
             files.push(item.getAsFile());
         }
         uploadFiles(files);
+        for (const directory of directories) {
+            collectDirectory(directory, []).then((entries) => {
+                uploadDirectory(directory.name, entries);
+            });
+        }
     });
 
     document.querySelector('.clear > .session').addEventListener('click', () => {
@@ -0,0 +1,193 @@
+function request(method, path, onSuccess, headers = {}, body = null) {
+    const req = new XMLHttpRequest();
+    req.open(method, path, true);
+    req.setRequestHeader('Accept', 'application/json');
+    for (const [name, value] of Object.entries(headers)) {
+        req.setRequestHeader(name, value);
+    }
+    req.responseType = 'json';
+    req.onload = () => {
+        if (req.status === 401) {
+            window.location = '/login/';
+        } else if (req.status >= 200 && req.status < 300) {
+            onSuccess(req.response);
+        } else {
+            alert(`An error occurred: ${req.response.error}.`);
+            console.error(`An error occurred: ${req.response.error}.`);
+        }
+    };
+    req.send(body);
+}
+
+// Applies an operation to every selected file in a single request, reporting the failed ones.
+function batch(operation, aliases) {
+    request('POST', '/batch', (response) => {
+        const failed = response.results.filter((result) => !result.success);
+        if (failed.length > 0) {
+            alert(failed.map((result) => `${result.alias}: ${result.error}`).join('\n'));
+        }
+        document.querySelector('.toggle-trash').addEventListener('click', (event) => {
+    trashed = !trashed;
+    event.target.innerText = trashed ? 'Show uploads' : 'Show trash';
+    load();
+});
+
+load();
+    }, { 'Content-Type': 'application/json' }, JSON.stringify({
+        operation,
+        files: aliases.map((alias) => ({ alias })),
+    }));
+}
+
+// Trashed files are listed separately, only to be restored.
+let trashed = false;
+
+function selectedAliases() {
+    return [...document.querySelectorAll('.files tr')]
+        .filter((row) => row.querySelector('input[type=checkbox]').checked)
+        .map((row) => row.dataset.alias);
+}
+
+function load() {
+    request('GET', trashed ? '/uploads/files?trashed=true' : '/uploads/files', (response) => {
+        const table = document.querySelector('.files');
+        table.innerHTML = '';
+        const size = response.files.reduce((sum, file) => sum + file.size.bytes, 0);
+        document.querySelector('.details').innerText = `${response.files.length} ${'file'.plural(response.files.length)}, ${bytesReadable(size)} in total`;
+        for (const file of response.files) {
+            table.append(row(file));
+        }
+    });
+}
+
+function row(file) {
+    const row = document.createElement('tr');
+    row.dataset.alias = file.alias.short;
+
+    const select = document.createElement('td');
+    const checkbox = document.createElement('input');
+    checkbox.type = 'checkbox';
+    select.append(checkbox);
+
+    const name = document.createElement('td');
+    name.classList.add('name');
+    const link = document.createElement('a');
+    link.href = file.link.short;
+    link.innerText = file.name;
+    name.append(link);
+
+    const size = document.createElement('td');
+    size.classList.add('secondary');
+    size.innerText = file.size.readable;
+
+    const expiration = document.createElement('td');
+    expiration.classList.add('secondary');
+    expiration.innerText = trashed ? 'Trashed' : `Expires in ${file.expiration.duration.readable}`;
+
+    const downloads = document.createElement('td');
+    downloads.classList.add('secondary');
+    downloads.innerText = file.downloads === null ? 'Unlimited' : `${file.downloads} ${'download'.plural(file.downloads)} left`;
+
+    const operations = document.createElement('td');
+    operations.classList.add('operations');
+    if (trashed) {
+        operations.append(operation('Restore', () => {
+            request('PATCH', `/${file.alias.short}/restore`, load);
+        }));
+        row.append(select, name, size, expiration, downloads, operations);
+        return row;
+    }
+    // Files larger than the current thresholds cannot be extended.
+    if (file.admin.allowed !== null) {
+        operations.append(operation('Extend', () => {
+            request('PATCH', `/${file.alias.short}/expiration/${file.admin.allowed.seconds}`, load);
+        }));
+    }
+    operations.append(
+        operation('Rename', () => {
+            const name = prompt('New name:', file.name);
+            if (name !== null && name !== '') {
+                request('PATCH', `/${file.alias.short}/name`, load, { 'X-Filename': encodeURIComponent(name) });
+            }
+        }),
+        operation('Describe', () => {
+            const description = prompt('Description:', file.description ?? '');
+            if (description === null) {
+                return;
+            }
+            const tags = prompt('Tags (comma separated):', file.tags.join(', '));
+            if (tags !== null) {
+                request('PATCH', `/${file.alias.short}/details`, load, {
+                    'X-Description': encodeURIComponent(description),
+                    'X-Tags': encodeURIComponent(tags),
+                });
+            }
+        }),
+        operation('New alias', () => {
+            if (confirm('Generating new aliases will make all people with a current link unable to access it. Confirm?')) {
+                request('PATCH', `/${file.alias.short}/alias`, load);
+            }
+        }),
+        operation('Limit downloads', () => {
+            const count = prompt('Number of downloads allowed (0 for unlimited):', '1');
+            if (count !== null && /^\d+$/.test(count)) {
+                request('PATCH', `/${file.alias.short}/downloads/${count}`, load);
+            }
+        }),
+        operation('Revoke', () => {
+            if (confirm(`Revoke ${file.name}?`)) {
+                request('DELETE', `/${file.alias.short}`, load);
+            }
+        }, 'revoke'),
+    );
+
+    row.append(select, name, size, expiration, downloads, operations);
+    return row;
+}
+
+function operation(label, onClick, ...classes) {
+    const operation = document.createElement('div');
+    operation.classList.add('clickable', ...classes);
+    operation.innerText = label;
+    operation.addEventListener('click', onClick);
+    return operation;
+}
+
+function bytesReadable(bytes) {
+    const units = ['B', 'KB', 'MB', 'GB', 'TB'];
+    let unit = 0;
+    while (bytes >= 1000 && unit < units.length - 1) {
+        bytes /= 1000;
+        unit += 1;
+    }
+    return `${bytes.toFixed(unit === 0 ? 0 : 2)} ${units[unit]}`;
+}
+
+String.prototype.plural = function (n) {
+    return n >= 2 ? `${this}s` : this;
+};
+
+document.querySelector('.select-all').addEventListener('click', () => {
+    const checkboxes = [...document.querySelectorAll('.files input[type=checkbox]')];
+    const checked = !checkboxes.every((checkbox) => checkbox.checked);
+    checkboxes.forEach((checkbox) => checkbox.checked = checked);
+});
+
+document.querySelector('.extend-selected').addEventListener('click', () => {
+    const aliases = selectedAliases();
+    if (aliases.length > 0) {
+        batch({ type: 'extend', duration: 'max' }, aliases);
+    }
+});
+
+document.querySelector('.delete-selected').addEventListener('click', () => {
+    const aliases = selectedAliases();
+    if (aliases.length === 0) {
+        return;
+    }
+    if (confirm(`Revoke ${aliases.length} ${'file'.plural(aliases.length)}?`)) {
+        batch({ type: 'revoke' }, aliases);
+    }
+});
+
+load();
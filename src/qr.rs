@@ -0,0 +1,113 @@
+use std::io::Cursor;
+
+use image::{GrayImage, ImageOutputFormat, Luma};
+use qrcode::{types::Color, EcLevel, QrCode};
+
+// Margin required by scanners around the code, in modules.
+const QUIET_ZONE: usize = 4;
+const PNG_MODULE_SIZE: u32 = 8;
+
+pub struct Qr {
+    width: usize,
+    dark: Vec<bool>,
+}
+
+impl Qr {
+    pub fn new(data: &str) -> Option<Self> {
+        let code = QrCode::with_error_correction_level(data, EcLevel::M).ok()?;
+        Some(Self {
+            width: code.width(),
+            dark: code
+                .to_colors()
+                .into_iter()
+                .map(|color| color == Color::Dark)
+                .collect(),
+        })
+    }
+
+    // Width including the quiet zone on both sides.
+    fn size(&self) -> usize {
+        self.width + 2 * QUIET_ZONE
+    }
+
+    // Coordinates include the quiet zone.
+    fn is_dark(&self, x: usize, y: usize) -> bool {
+        match (x.checked_sub(QUIET_ZONE), y.checked_sub(QUIET_ZONE)) {
+            (Some(x), Some(y)) if x < self.width && y < self.width => self.dark[y * self.width + x],
+            _ => false,
+        }
+    }
+
+    pub fn png(&self) -> Option<Vec<u8>> {
+        let size = self.size() as u32 * PNG_MODULE_SIZE;
+        let image = GrayImage::from_fn(size, size, |x, y| {
+            if self.is_dark(
+                (x / PNG_MODULE_SIZE) as usize,
+                (y / PNG_MODULE_SIZE) as usize,
+            ) {
+                Luma([0])
+            } else {
+                Luma([255])
+            }
+        });
+        let mut png = Vec::new();
+        image
+            .write_to(&mut Cursor::new(&mut png), ImageOutputFormat::Png)
+            .ok()?;
+        Some(png)
+    }
+
+    pub fn svg(&self) -> String {
+        let size = self.size();
+        let path = (0..size)
+            .flat_map(|y| (0..size).map(move |x| (x, y)))
+            .filter(|&(x, y)| self.is_dark(x, y))
+            .map(|(x, y)| format!("M{},{}h1v1h-1z", x, y))
+            .collect::<String>();
+        format!(
+            r##"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 {0} {0}" shape-rendering="crispEdges"><rect width="{0}" height="{0}" fill="#ffffff"/><path fill="#000000" d="{1}"/></svg>"##,
+            size, path,
+        )
+    }
+
+    // Each character draws two modules using a half block, with explicit colors so it scans on dark terminals too.
+    pub fn text(&self) -> String {
+        let size = self.size();
+        (0..size)
+            .step_by(2)
+            .map(|y| {
+                let line = (0..size)
+                    .map(|x| {
+                        format!(
+                            "\x1b[{};{}m\u{2580}",
+                            if self.is_dark(x, y) { 30 } else { 97 },
+                            if self.is_dark(x, y + 1) { 40 } else { 107 },
+                        )
+                    })
+                    .collect::<String>();
+                format!("{}\x1b[0m", line)
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Qr, QUIET_ZONE};
+
+    #[test]
+    fn rendered() {
+        let qr = Qr::new("https://drop.it/aBcDeF").unwrap();
+        assert_eq!(qr.width, 25);
+        // Top left finder pattern corner, after the quiet zone.
+        assert!(!qr.is_dark(QUIET_ZONE - 1, QUIET_ZONE));
+        assert!(qr.is_dark(QUIET_ZONE, QUIET_ZONE));
+        assert!(!qr.is_dark(qr.size(), qr.size()));
+
+        let png = image::load_from_memory(&qr.png().unwrap()).unwrap();
+        assert_eq!(png.width(), 33 * 8);
+        assert!(qr.svg().starts_with("<svg"));
+        assert_eq!(qr.text().lines().count(), 17);
+    }
+}
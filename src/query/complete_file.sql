@@ -0,0 +1,3 @@
+UPDATE files
+SET state = 'complete', compression = ?, mime = ?, language = ?
+WHERE id = ?;
\ No newline at end of file
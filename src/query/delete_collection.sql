@@ -0,0 +1,2 @@
+DELETE FROM collections
+WHERE id = ?;
\ No newline at end of file
@@ -0,0 +1,2 @@
+DELETE FROM files
+WHERE state = 'pending';
\ No newline at end of file
@@ -1,3 +1,7 @@
 SELECT 1
 FROM files
-WHERE long_alias = ?;
\ No newline at end of file
+WHERE long_alias = ?1
+UNION ALL
+SELECT 1
+FROM collections
+WHERE long_alias = ?1;
\ No newline at end of file
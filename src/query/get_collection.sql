@@ -0,0 +1,3 @@
+SELECT id, IFNULL(name, long_alias) AS name, short_alias, expiration, downloads
+FROM collections
+WHERE short_alias = ? OR long_alias = ?;
\ No newline at end of file
@@ -0,0 +1,3 @@
+SELECT id, admin
+FROM collections
+WHERE short_alias = ? OR long_alias = ?;
\ No newline at end of file
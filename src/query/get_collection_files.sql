@@ -0,0 +1,5 @@
+SELECT files.id, IFNULL(files.name, files.long_alias) AS name, files.size, files.compression, files.mime, files.language, files.downloads, files.expiration, IFNULL(files.modified, files.uploaded) AS modified, files.short_alias, files.long_alias, files.description, files.tags
+FROM collection_files
+JOIN files ON files.id = collection_files.file_id
+WHERE collection_files.collection_id = ? AND files.state = 'complete'
+ORDER BY collection_files.position;
\ No newline at end of file
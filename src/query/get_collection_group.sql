@@ -0,0 +1,3 @@
+SELECT id, short_alias, long_alias
+FROM collections
+WHERE admin = ?1 AND origin = ?2 AND name = ?3;
\ No newline at end of file
@@ -0,0 +1,4 @@
+SELECT IFNULL(SUM(files.size), 0) AS size
+FROM collection_files
+JOIN files ON files.id = collection_files.file_id
+WHERE collection_files.collection_id = ?;
\ No newline at end of file
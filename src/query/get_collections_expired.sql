@@ -0,0 +1,4 @@
+SELECT id
+FROM collections
+WHERE expiration < ?
+   OR NOT EXISTS(SELECT 1 FROM collection_files WHERE collection_id = collections.id);
\ No newline at end of file
@@ -0,0 +1,10 @@
+SELECT deadline
+FROM (SELECT MIN(expiration, IFNULL(IFNULL(last_activity, uploaded) + inactivity, expiration)) AS deadline
+      FROM files
+      WHERE state != 'trashed'
+      UNION
+      SELECT expiration AS deadline
+      FROM collections)
+WHERE deadline >= ?1
+ORDER BY deadline
+LIMIT ?2;
\ No newline at end of file
@@ -1,3 +1,3 @@
-SELECT id, IFNULL(name, long_alias) AS name, size
+SELECT id, IFNULL(name, long_alias) AS name, size, compression, mime, language, downloads, expiration, IFNULL(modified, uploaded) AS modified, short_alias, long_alias, description, tags
 FROM files
-WHERE short_alias = ? OR long_alias = ?;
\ No newline at end of file
+WHERE (short_alias = ? OR long_alias = ?) AND state = 'complete';
\ No newline at end of file
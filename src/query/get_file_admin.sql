@@ -1,3 +1,3 @@
-SELECT id, size, admin
+SELECT id, size, admin, origin
 FROM files
-WHERE short_alias = ? OR long_alias = ?;
\ No newline at end of file
+WHERE (short_alias = ? OR long_alias = ?) AND state = 'complete';
\ No newline at end of file
@@ -0,0 +1,3 @@
+SELECT id, origin, size, admin
+FROM files
+WHERE (short_alias = ? OR long_alias = ?) AND state = 'complete';
\ No newline at end of file
@@ -0,0 +1,3 @@
+SELECT IFNULL(name, long_alias) AS name, size, mime, language, downloads, expiration, uploaded, short_alias, long_alias, admin, origin, description, tags, inactivity
+FROM files
+WHERE (short_alias = ? OR long_alias = ?) AND state = 'complete';
\ No newline at end of file
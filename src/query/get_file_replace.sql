@@ -0,0 +1,3 @@
+SELECT id, admin, origin, expiration, name, size, short_alias, long_alias, downloads
+FROM files
+WHERE (short_alias = ? OR long_alias = ?) AND state = 'complete';
\ No newline at end of file
@@ -0,0 +1,3 @@
+SELECT id, size, admin, origin, expiration
+FROM files
+WHERE (short_alias = ? OR long_alias = ?) AND state = 'trashed';
\ No newline at end of file
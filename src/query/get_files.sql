@@ -0,0 +1,2 @@
+SELECT id, size, state IN ('complete', 'trashed') AS stored, compression IS NOT NULL AS compressed
+FROM files;
\ No newline at end of file
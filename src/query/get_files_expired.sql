@@ -1,3 +1,5 @@
 SELECT id
 FROM files
-WHERE expiration < ?;
\ No newline at end of file
+WHERE state != 'trashed'
+  AND (expiration < ?1
+   OR (inactivity IS NOT NULL AND IFNULL(last_activity, uploaded) + inactivity < ?1));
\ No newline at end of file
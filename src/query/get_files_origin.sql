@@ -0,0 +1,4 @@
+SELECT IFNULL(name, long_alias) AS name, size, mime, language, downloads, expiration, uploaded, short_alias, long_alias, admin, origin, description, tags, inactivity
+FROM files
+WHERE origin = ?1 AND state = CASE WHEN ?2 THEN 'trashed' ELSE 'complete' END
+ORDER BY uploaded DESC;
\ No newline at end of file
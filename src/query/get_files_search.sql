@@ -0,0 +1,11 @@
+SELECT IFNULL(name, long_alias) AS name, size, mime, language, downloads, expiration, uploaded, short_alias, long_alias, admin, origin, description, tags, inactivity
+FROM files
+WHERE state = CASE WHEN ?9 THEN 'trashed' ELSE 'complete' END
+    AND (?1 IS NULL OR origin = ?1)
+    AND (?2 IS NULL OR IFNULL(name, long_alias) LIKE ?2 ESCAPE '\')
+    AND (?3 IS NULL OR size >= ?3)
+    AND (?4 IS NULL OR size <= ?4)
+    AND (?5 IS NULL OR IFNULL(uploaded, 0) <= ?5)
+    AND (?6 IS NULL OR uploaded >= ?6)
+ORDER BY uploaded DESC
+LIMIT ?7 OFFSET ?8;
\ No newline at end of file
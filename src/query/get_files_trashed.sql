@@ -0,0 +1,3 @@
+SELECT id
+FROM files
+WHERE state = 'trashed' AND trashed < ?;
\ No newline at end of file
@@ -0,0 +1,3 @@
+SELECT IFNULL(SUM(size), 0) AS size
+FROM files
+WHERE state = 'pending';
\ No newline at end of file
@@ -0,0 +1,4 @@
+SELECT origin, SUM(size) AS size, COUNT(*) AS files
+FROM files
+GROUP BY origin
+ORDER BY size DESC;
\ No newline at end of file
@@ -0,0 +1,5 @@
+SELECT (SELECT COUNT(*) FROM files WHERE state = 'complete') AS files,
+    (SELECT COUNT(*) FROM files WHERE state = 'pending') AS pending,
+    (SELECT COUNT(*) FROM files WHERE state = 'trashed') AS trashed,
+    (SELECT COUNT(*) FROM collections) AS collections,
+    (SELECT IFNULL(SUM(size), 0) FROM files) AS size;
\ No newline at end of file
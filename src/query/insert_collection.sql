@@ -0,0 +1,2 @@
+INSERT INTO collections (id, admin, origin, expiration, name, short_alias, long_alias)
+VALUES (?, ?, ?, ?, ?, ?, ?);
\ No newline at end of file
@@ -0,0 +1,2 @@
+INSERT INTO collection_files (collection_id, file_id, position)
+VALUES (?, ?, ?);
\ No newline at end of file
@@ -0,0 +1,4 @@
+INSERT INTO collection_files (collection_id, file_id, position)
+SELECT ?1, ?2, IFNULL(MAX(position) + 1, 0)
+FROM collection_files
+WHERE collection_id = ?1;
\ No newline at end of file
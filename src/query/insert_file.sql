@@ -1,2 +1,2 @@
-INSERT INTO files (id, admin, origin, expiration, name, size, short_alias, long_alias)
-VALUES (?, ?, ?, ?, ?, ?, ?, ?);
\ No newline at end of file
+INSERT INTO files (id, admin, origin, expiration, name, size, short_alias, long_alias, uploaded, modified, description, tags, state)
+VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, 'pending');
\ No newline at end of file
@@ -0,0 +1,2 @@
+ALTER TABLE files
+ADD COLUMN state TEXT NOT NULL DEFAULT 'complete';
\ No newline at end of file
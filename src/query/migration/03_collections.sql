@@ -0,0 +1,17 @@
+CREATE TABLE collections (
+    id TEXT NOT NULL PRIMARY KEY,
+    admin TEXT NOT NULL,
+    origin TEXT NOT NULL,
+    expiration INTEGER NOT NULL,
+    name TEXT,
+    short_alias TEXT NOT NULL,
+    long_alias TEXT NOT NULL,
+    downloads INTEGER
+);
+
+CREATE TABLE collection_files (
+    collection_id TEXT NOT NULL REFERENCES collections (id) ON DELETE CASCADE,
+    file_id TEXT NOT NULL REFERENCES files (id) ON DELETE CASCADE,
+    position INTEGER NOT NULL,
+    PRIMARY KEY (collection_id, file_id)
+);
\ No newline at end of file
@@ -0,0 +1,5 @@
+ALTER TABLE files
+ADD COLUMN uploaded INTEGER;
+
+ALTER TABLE files
+ADD COLUMN modified INTEGER;
\ No newline at end of file
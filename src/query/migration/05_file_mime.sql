@@ -0,0 +1,2 @@
+ALTER TABLE files
+ADD COLUMN mime TEXT;
\ No newline at end of file
@@ -0,0 +1,5 @@
+ALTER TABLE files
+ADD COLUMN description TEXT;
+
+ALTER TABLE files
+ADD COLUMN tags TEXT;
\ No newline at end of file
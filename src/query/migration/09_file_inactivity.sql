@@ -0,0 +1,5 @@
+ALTER TABLE files
+ADD COLUMN inactivity INTEGER;
+
+ALTER TABLE files
+ADD COLUMN last_activity INTEGER;
\ No newline at end of file
@@ -0,0 +1,2 @@
+ALTER TABLE files
+ADD COLUMN trashed INTEGER;
\ No newline at end of file
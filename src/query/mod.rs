@@ -1,3 +1,7 @@
+use sqlx::{Connection, Sqlite, SqlitePool, Transaction};
+
+use crate::update::hash_admin_token;
+
 #[macro_export]
 macro_rules! include_query {
     ($name:expr) => {
@@ -9,3 +13,65 @@ macro_rules! include_query {
         ))
     };
 }
+
+enum Migration {
+    Query(&'static str),
+    // SQLite cannot compute digests, so existing tokens are hashed from here.
+    HashAdminTokens,
+}
+
+// Applied in order on top of the initial schema, tracked using SQLite's user_version.
+const MIGRATIONS: &[Migration] = &[
+    Migration::Query(include_query!("migration/01_file_state")),
+    Migration::Query(include_query!("migration/02_file_compression")),
+    Migration::Query(include_query!("migration/03_collections")),
+    Migration::Query(include_query!("migration/04_file_times")),
+    Migration::Query(include_query!("migration/05_file_mime")),
+    Migration::Query(include_query!("migration/06_file_language")),
+    Migration::Query(include_query!("migration/07_file_details")),
+    Migration::HashAdminTokens,
+    Migration::Query(include_query!("migration/09_file_inactivity")),
+    Migration::Query(include_query!("migration/10_file_trash")),
+];
+
+pub async fn migrate(pool: &SqlitePool) -> Result<(), sqlx::Error> {
+    let mut conn = pool.acquire().await?;
+    sqlx::query(include_query!("migration"))
+        .execute(&mut conn)
+        .await?;
+
+    let (version,) = sqlx::query_as::<_, (i64,)>("PRAGMA user_version")
+        .fetch_one(&mut conn)
+        .await?;
+    for (index, migration) in MIGRATIONS.iter().enumerate().skip(version as usize) {
+        let mut tx = conn.begin().await?;
+        match migration {
+            Migration::Query(query) => {
+                sqlx::query(query).execute(&mut tx).await?;
+            }
+            Migration::HashAdminTokens => hash_admin_tokens(&mut tx).await?,
+        }
+        sqlx::query(&format!("PRAGMA user_version = {}", index + 1))
+            .execute(&mut tx)
+            .await?;
+        tx.commit().await?;
+    }
+    Ok(())
+}
+
+async fn hash_admin_tokens(tx: &mut Transaction<'_, Sqlite>) -> Result<(), sqlx::Error> {
+    for table in ["files", "collections"] {
+        let tokens =
+            sqlx::query_as::<_, (String, String)>(&format!("SELECT id, admin FROM {}", table))
+                .fetch_all(&mut *tx)
+                .await?;
+        for (id, admin) in tokens {
+            sqlx::query(&format!("UPDATE {} SET admin = ? WHERE id = ?", table))
+                .bind(hash_admin_token(&admin))
+                .bind(id)
+                .execute(&mut *tx)
+                .await?;
+        }
+    }
+    Ok(())
+}
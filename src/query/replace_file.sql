@@ -0,0 +1,3 @@
+UPDATE files
+SET id = ?, size = ?, name = ?, modified = ?, uploaded = ?, compression = ?, mime = ?, language = ?, expiration = ?, downloads = ?
+WHERE id = ? AND state = 'complete';
\ No newline at end of file
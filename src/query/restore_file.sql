@@ -0,0 +1,3 @@
+UPDATE files
+SET state = 'complete', trashed = NULL, expiration = ?1, last_activity = ?2
+WHERE id = ?3 AND state = 'trashed';
\ No newline at end of file
@@ -0,0 +1,3 @@
+UPDATE files
+SET state = 'trashed', trashed = ?
+WHERE id = ?;
\ No newline at end of file
@@ -0,0 +1,3 @@
+UPDATE collections
+SET downloads = ?
+WHERE id = ?;
\ No newline at end of file
@@ -0,0 +1,3 @@
+UPDATE collection_files
+SET file_id = ?
+WHERE file_id = ?;
\ No newline at end of file
@@ -0,0 +1,3 @@
+UPDATE files
+SET last_activity = ?
+WHERE id = ?;
\ No newline at end of file
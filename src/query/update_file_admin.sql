@@ -0,0 +1,3 @@
+UPDATE files
+SET admin = ?
+WHERE id = ?;
\ No newline at end of file
@@ -0,0 +1,3 @@
+UPDATE files
+SET description = CASE WHEN ?1 THEN ?2 ELSE description END, tags = CASE WHEN ?3 THEN ?4 ELSE tags END
+WHERE id = ?5;
\ No newline at end of file
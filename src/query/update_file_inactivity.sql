@@ -0,0 +1,3 @@
+UPDATE files
+SET inactivity = ?1, last_activity = ?2
+WHERE id = ?3;
\ No newline at end of file
@@ -32,18 +32,13 @@ impl SingleLine for () {
     }
 }
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Default, Debug)]
 pub enum ResponseType {
+    #[default]
     Json,
     Text,
 }
 
-impl Default for ResponseType {
-    fn default() -> Self {
-        Self::Json
-    }
-}
-
 impl AsNegotiationStr for ResponseType {
     fn as_str(&self) -> &str {
         match self {
@@ -1,30 +1,180 @@
 use std::{
     io::ErrorKind,
+    sync::Arc,
     time::{Duration, SystemTime, UNIX_EPOCH},
 };
 
-use sqlx::SqlitePool;
+use sqlx::{SqliteConnection, SqlitePool};
 
-use crate::{include_query, storage::dir::Dir};
+use crate::{
+    error::{revoke as RemoveError, Error},
+    include_query,
+    storage::{
+        dir::Dir,
+        schedule::{Scheduler, SCHEDULE_CAPACITY},
+    },
+};
+
+const PURGE_INTERVAL: Duration = Duration::from_secs(600);
+// Full rescan of upcoming deadlines, in case some changes weren't scheduled.
+const RESCAN_INTERVAL: Duration = Duration::from_secs(3600);
+const RETRY_INTERVAL: Duration = Duration::from_secs(60);
 
 pub struct Cleaner {
     dir: Dir,
     pool: SqlitePool,
+    trash_grace: Duration,
+    scheduler: Arc<Scheduler>,
 }
 
 impl Cleaner {
-    pub fn new(dir: Dir, pool: SqlitePool) -> Self {
-        Self { dir, pool }
+    pub fn new(
+        dir: Dir,
+        pool: SqlitePool,
+        trash_grace: Duration,
+        scheduler: Arc<Scheduler>,
+    ) -> Self {
+        Self {
+            dir,
+            pool,
+            trash_grace,
+            scheduler,
+        }
+    }
+
+    // Pending uploads cannot survive a restart, so their rows and partial bodies are discarded.
+    pub async fn clean_pending(&self) {
+        match self.dir.clear_staging().await {
+            Ok(0) => (),
+            Ok(count) => log::warn!("Removed {} partially uploaded file(s)", count),
+            Err(err) => log::error!("Cannot clear uploads staging directory: {}", err),
+        }
+        if let Err(err) = sqlx::query(include_query!("delete_files_pending"))
+            .execute(&self.pool)
+            .await
+        {
+            log::error!("Cannot remove pending files from database: {:?}", err);
+        }
     }
 
+    // Single removal path for expired, revoked, moderated and fully downloaded files.
+    pub async fn remove_file(&self, id: &str, conn: &mut SqliteConnection) -> Result<(), Error> {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_err(|_| RemoveError::TimeCalculation)?
+            .as_secs();
+        self.discard_file(id, now, conn).await?;
+        // Collections left without files are removed by the next sweep.
+        self.scheduler.schedule(now);
+        Ok(())
+    }
+
+    // With a grace period, files are only trashed: their links stop working at once,
+    // but their content is kept until purged, so they can still be restored.
+    async fn discard_file(
+        &self,
+        id: &str,
+        now: u64,
+        conn: &mut SqliteConnection,
+    ) -> Result<(), Error> {
+        if self.trash_grace.is_zero() {
+            return self.purge_file(id, conn).await;
+        }
+        sqlx::query(include_query!("trash_file"))
+            .bind(now as i64)
+            .bind(id)
+            .execute(conn)
+            .await
+            .map_err(|err| {
+                log::error!("Cannot move file with id {} to the trash: {}", id, err);
+                RemoveError::Database
+            })?;
+        Ok(())
+    }
+
+    async fn purge_file(&self, id: &str, conn: &mut SqliteConnection) -> Result<(), Error> {
+        if let Err(err) = self.dir.delete_file(id).await {
+            if err.kind() == ErrorKind::NotFound {
+                log::warn!(
+                    "File with id {} already deleted of absent from storage directory",
+                    id
+                );
+            } else {
+                log::error!(
+                    "Cannot remove file with id {} from file system: {}",
+                    id,
+                    err
+                );
+                return Err(RemoveError::RemoveFile);
+            }
+        }
+        sqlx::query(include_query!("delete_file"))
+            .bind(id)
+            .execute(conn)
+            .await
+            .map_err(|err| {
+                log::error!("Cannot remove file with id {} from database: {}", id, err);
+                RemoveError::PartialRemove
+            })?;
+        Ok(())
+    }
+
+    // Sleeps until the earliest known deadline, unless an earlier one gets scheduled meanwhile.
     pub async fn start(&self) {
         loop {
-            self.clean_expires().await;
-            tokio::time::sleep(Duration::from_secs(60)).await;
+            match self.clean_expires().await {
+                Some(swept) => {
+                    let upcoming = self.upcoming_deadlines(swept).await;
+                    self.scheduler.reload(swept, upcoming);
+                }
+                None => {
+                    tokio::time::sleep(RETRY_INTERVAL).await;
+                    continue;
+                }
+            }
+            loop {
+                let wait = match SystemTime::now().duration_since(UNIX_EPOCH) {
+                    Ok(now) => self.scheduler.wait(now.as_secs(), RESCAN_INTERVAL),
+                    Err(_) => RETRY_INTERVAL,
+                };
+                if wait.is_zero() {
+                    break;
+                }
+                tokio::select! {
+                    _ = tokio::time::sleep(wait) => break,
+                    _ = self.scheduler.notified() => (),
+                }
+            }
+        }
+    }
+
+    async fn upcoming_deadlines(&self, from: u64) -> Vec<u64> {
+        match sqlx::query_as::<_, (i64,)>(include_query!("get_deadlines"))
+            .bind(from as i64)
+            .bind(SCHEDULE_CAPACITY as i64)
+            .fetch_all(&self.pool)
+            .await
+        {
+            Ok(deadlines) => deadlines
+                .into_iter()
+                .map(|(deadline,)| deadline.max(0) as u64)
+                .collect(),
+            Err(err) => {
+                log::error!("Cannot fetch upcoming expiration deadlines: {:?}", err);
+                Vec::new()
+            }
+        }
+    }
+
+    pub async fn start_purge(&self) {
+        loop {
+            self.purge_trash().await;
+            tokio::time::sleep(PURGE_INTERVAL).await;
         }
     }
 
-    async fn clean_expires(&self) {
+    // Also empties the trash when the grace period got disabled since files were trashed.
+    async fn purge_trash(&self) {
         let mut conn = match self.pool.acquire().await {
             Ok(conn) => conn,
             Err(err) => {
@@ -33,14 +183,54 @@ impl Cleaner {
             }
         };
 
+        let limit = match SystemTime::now()
+            .checked_sub(self.trash_grace)
+            .and_then(|limit| limit.duration_since(UNIX_EPOCH).ok())
+        {
+            Some(limit) => limit.as_secs(),
+            None => {
+                log::error!("Cannot calculate trash grace period limit");
+                return;
+            }
+        };
+
+        let files = match sqlx::query_as::<_, (String,)>(include_query!("get_files_trashed"))
+            .bind(limit as i64)
+            .fetch_all(&mut conn)
+            .await
+        {
+            Ok(files) => files,
+            Err(err) => {
+                log::error!("Cannot fetch trashed files: {:?}", err);
+                return;
+            }
+        };
+
+        // Failures are logged by the removal itself, the file is retried on the next pass.
+        for (id,) in files {
+            let _ = self.purge_file(&id, &mut conn).await;
+        }
+    }
+
+    // Returns the timestamp entries were expired against, if the sweep completed.
+    async fn clean_expires(&self) -> Option<u64> {
+        let mut conn = match self.pool.acquire().await {
+            Ok(conn) => conn,
+            Err(err) => {
+                log::error!("Cannot acquire database connection: {:?}", err);
+                return None;
+            }
+        };
+
         let now_timestamp = match SystemTime::now().duration_since(UNIX_EPOCH) {
             Ok(timestamp) => timestamp.as_secs(),
             Err(err) => {
                 log::error!("Cannot generate timestamp: {}", err);
-                return;
+                return None;
             }
         };
 
+        // Past their expiration date, or left undownloaded longer than their inactivity period.
         let files = match sqlx::query_as::<_, (String,)>(include_query!("get_files_expired"))
             .bind(now_timestamp as i64)
             .fetch_all(&mut conn)
@@ -49,35 +239,41 @@ impl Cleaner {
             Ok(files) => files,
             Err(err) => {
                 log::error!("Cannot fetch expired files: {:?}", err);
-                return;
+                return None;
             }
         };
 
-        if !files.is_empty() {
-            for (id,) in files {
-                if let Err(err) = self.dir.delete_file(&id).await {
-                    if err.kind() == ErrorKind::NotFound {
-                        log::warn!(
-                            "File with id {} already deleted of absent from storage directory",
-                            id
-                        );
-                    } else {
-                        log::error!(
-                            "Cannot remove file with id {} from file system: {}",
-                            id,
-                            err
-                        );
-                        continue;
-                    }
-                }
-                if let Err(err) = sqlx::query(include_query!("delete_file"))
-                    .bind(&id)
-                    .execute(&mut conn)
-                    .await
-                {
-                    log::error!("Cannot remove file with id {} from database: {}", id, err);
+        // Failures are logged by the removal itself, the file is retried by a later sweep.
+        for (id,) in files {
+            let _ = self.discard_file(&id, now_timestamp, &mut conn).await;
+        }
+
+        // Collections only reference files, so their removal doesn't touch the storage directory.
+        let collections =
+            match sqlx::query_as::<_, (String,)>(include_query!("get_collections_expired"))
+                .bind(now_timestamp as i64)
+                .fetch_all(&mut conn)
+                .await
+            {
+                Ok(collections) => collections,
+                Err(err) => {
+                    log::error!("Cannot fetch expired collections: {:?}", err);
+                    return None;
                 }
+            };
+        for (id,) in collections {
+            if let Err(err) = sqlx::query(include_query!("delete_collection"))
+                .bind(&id)
+                .execute(&mut conn)
+                .await
+            {
+                log::error!(
+                    "Cannot remove collection with id {} from database: {}",
+                    id,
+                    err
+                );
             }
         }
+        Some(now_timestamp)
     }
 }
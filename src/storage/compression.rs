@@ -0,0 +1,41 @@
+use async_compression::tokio::{bufread::ZstdDecoder, write::ZstdEncoder};
+use tokio::io::{AsyncRead, AsyncWriteExt, BufReader};
+
+// Ratio (compressed / original) of the body sample above which compression isn't worth it.
+const MAX_SAMPLE_RATIO: f64 = 0.9;
+
+#[derive(sqlx::Type, Copy, Clone, PartialEq, Eq, Debug)]
+#[sqlx(rename_all = "lowercase")]
+pub enum Compression {
+    Zstd,
+}
+
+impl Compression {
+    pub fn encoding(&self) -> &'static str {
+        match self {
+            Compression::Zstd => "zstd",
+        }
+    }
+
+    pub async fn is_worth(&self, sample: &[u8]) -> bool {
+        if sample.is_empty() {
+            return false;
+        }
+        let mut encoder = match self {
+            Compression::Zstd => ZstdEncoder::new(Vec::with_capacity(sample.len())),
+        };
+        if encoder.write_all(sample).await.is_err() || encoder.shutdown().await.is_err() {
+            return false;
+        }
+        (encoder.get_ref().len() as f64 / sample.len() as f64) < MAX_SAMPLE_RATIO
+    }
+
+    pub fn decoder<R>(&self, reader: R) -> Box<dyn AsyncRead + Send + Unpin>
+    where
+        R: AsyncRead + Send + Unpin + 'static,
+    {
+        match self {
+            Compression::Zstd => Box::new(ZstdDecoder::new(BufReader::new(reader))),
+        }
+    }
+}
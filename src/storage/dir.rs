@@ -1,20 +1,51 @@
 use std::{
+    fs::Metadata,
+    future::Future,
     io::{Error as IoError, ErrorKind},
-    path::PathBuf,
+    path::{Path, PathBuf},
+    time::SystemTime,
 };
 
-use tokio::{fs, fs::File};
+use tokio::{fs, fs::File, io::AsyncRead};
+use uuid::Uuid;
+
+use crate::storage::Compression;
+
+const STAGING_DIR: &str = ".staging";
+const THUMBNAIL_SUFFIX: &str = ".thumb";
 
 #[derive(Clone, Debug)]
-pub struct Dir(PathBuf);
+pub struct Dir {
+    path: PathBuf,
+    shard_depth: usize,
+}
+
+#[derive(Debug)]
+pub struct StoredFile {
+    pub id: String,
+    pub path: PathBuf,
+    pub size: u64,
+    pub modified: SystemTime,
+}
+
+impl StoredFile {
+    // Thumbnails sit next to their file, whichever layout it was found in.
+    fn thumbnail_path(&self) -> PathBuf {
+        self.path
+            .with_file_name(format!("{}{}", self.id, THUMBNAIL_SUFFIX))
+    }
+}
 
 impl Dir {
-    pub fn new<P: Into<PathBuf>>(path: P) -> Self {
-        Self(path.into())
+    pub fn new<P: Into<PathBuf>>(path: P, shard_depth: usize) -> Self {
+        Self {
+            path: path.into(),
+            shard_depth,
+        }
     }
 
     pub async fn create(&self, should_create: bool) -> Result<(), &'static str> {
-        match File::open(&self.0).await {
+        match File::open(&self.path).await {
             Ok(fd) => match fd.metadata().await {
                 Ok(md) => {
                     if !md.is_dir() {
@@ -28,7 +59,7 @@ impl Dir {
             Err(err) => {
                 if err.kind() == ErrorKind::NotFound {
                     if should_create {
-                        if tokio::fs::create_dir_all(&self.0).await.is_err() {
+                        if tokio::fs::create_dir_all(&self.path).await.is_err() {
                             return Err("Cannot create uploads directory");
                         }
                     } else {
@@ -39,22 +70,310 @@ impl Dir {
                 }
             }
         }
+        if fs::create_dir_all(self.staging_path()).await.is_err() {
+            return Err("Cannot create uploads staging directory");
+        }
         Ok(())
     }
 
+    // Each shard level uses the next two characters of the id, e.g. ab/cd/abcd1234-….
     fn file_path(&self, id: &str) -> PathBuf {
-        self.0.join(id)
+        let mut path = self.path.clone();
+        for level in 0..self.shard_depth {
+            match id.get(level * 2..level * 2 + 2) {
+                Some(shard) => path.push(shard),
+                None => break,
+            }
+        }
+        path.join(id)
+    }
+
+    // Thumbnails share the shard of their file, e.g. ab/cd/abcd1234-….thumb.
+    fn thumbnail_path(&self, id: &str) -> PathBuf {
+        self.file_path(&format!("{}{}", id, THUMBNAIL_SUFFIX))
+    }
+
+    fn flat_file_path(&self, id: &str) -> PathBuf {
+        self.path.join(id)
     }
 
-    pub async fn create_file(&self, id: &str) -> Result<File, IoError> {
-        File::create(self.file_path(id)).await
+    // Upload files are named after their UUID, and stored either flat or in their shard.
+    // Anything else found in the uploads directory doesn't belong to the app.
+    pub fn is_blob(&self, file: &StoredFile) -> bool {
+        Uuid::parse_str(&file.id)
+            .map(|uuid| uuid.as_hyphenated().to_string() == file.id)
+            .unwrap_or(false)
+            && (file.path == self.file_path(&file.id) || file.path == self.flat_file_path(&file.id))
+    }
+
+    // Files may not have been moved to their shard yet, so fallback to the flat layout.
+    async fn with_file_path<T, F, Fut>(&self, id: &str, op: F) -> Result<T, IoError>
+    where
+        F: Fn(PathBuf) -> Fut,
+        Fut: Future<Output = Result<T, IoError>>,
+    {
+        let path = self.file_path(id);
+        if self.shard_depth == 0 {
+            return op(path).await;
+        }
+        match op(path.clone()).await {
+            Err(err) if err.kind() == ErrorKind::NotFound => {
+                match op(self.flat_file_path(id)).await {
+                    // The file may have been moved during the second attempt.
+                    Err(err) if err.kind() == ErrorKind::NotFound => op(path).await,
+                    res => res,
+                }
+            }
+            res => res,
+        }
+    }
+
+    fn staging_path(&self) -> PathBuf {
+        self.path.join(STAGING_DIR)
+    }
+
+    fn staging_file_path(&self, id: &str) -> PathBuf {
+        self.staging_path().join(id)
+    }
+
+    pub async fn create_staging_file(&self, id: &str) -> Result<File, IoError> {
+        File::create(self.staging_file_path(id)).await
+    }
+
+    // Staging directory lives inside the uploads directory, so the rename is atomic.
+    pub async fn commit_file(&self, id: &str) -> Result<(), IoError> {
+        let path = self.file_path(id);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).await?;
+        }
+        fs::rename(self.staging_file_path(id), path).await
+    }
+
+    pub async fn delete_staging_file(&self, id: &str) -> Result<(), IoError> {
+        fs::remove_file(self.staging_file_path(id)).await
+    }
+
+    pub async fn clear_staging(&self) -> Result<usize, IoError> {
+        let mut entries = fs::read_dir(self.staging_path()).await?;
+        let mut count = 0;
+        while let Some(entry) = entries.next_entry().await? {
+            fs::remove_file(entry.path()).await?;
+            count += 1;
+        }
+        Ok(count)
     }
 
     pub async fn open_file(&self, id: &str) -> Result<File, IoError> {
-        File::open(self.file_path(id)).await
+        self.with_file_path(id, File::open).await
+    }
+
+    pub async fn open_decompressed(
+        &self,
+        id: &str,
+        compression: Option<Compression>,
+    ) -> Result<Box<dyn AsyncRead + Send + Unpin>, IoError> {
+        let file = self.open_file(id).await?;
+        Ok(match compression {
+            Some(compression) => compression.decoder(file),
+            None => Box::new(file),
+        })
+    }
+
+    // Removes a listed file where it was found, whatever the current layout.
+    pub async fn delete_stored(&self, file: &StoredFile) -> Result<(), IoError> {
+        fs::remove_file(&file.path).await?;
+        match fs::remove_file(file.thumbnail_path()).await {
+            Err(err) if err.kind() != ErrorKind::NotFound => Err(err),
+            _ => Ok(()),
+        }
     }
 
     pub async fn delete_file(&self, id: &str) -> Result<(), IoError> {
-        fs::remove_file(self.file_path(id)).await
+        self.with_file_path(id, fs::remove_file).await?;
+        match fs::remove_file(self.thumbnail_path(id)).await {
+            Err(err) if err.kind() != ErrorKind::NotFound => Err(err),
+            _ => Ok(()),
+        }
+    }
+
+    pub async fn read_thumbnail(&self, id: &str) -> Result<Vec<u8>, IoError> {
+        fs::read(self.thumbnail_path(id)).await
+    }
+
+    // Concurrent generations of the same thumbnail each use their own staging file.
+    pub async fn write_thumbnail(&self, id: &str, thumbnail: &[u8]) -> Result<(), IoError> {
+        let staging = self.staging_file_path(&format!(
+            "{}-{}{}",
+            id,
+            Uuid::new_v4().as_simple(),
+            THUMBNAIL_SUFFIX
+        ));
+        fs::write(&staging, thumbnail).await?;
+        let path = self.thumbnail_path(id);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).await?;
+        }
+        fs::rename(staging, &path).await?;
+        // Deletions, including the previous content of replaced files, remove the file before
+        // its thumbnail, so either this check or the deletion gets rid of a stale one.
+        if self.file_metadata(id).await.is_err() {
+            match fs::remove_file(path).await {
+                Err(err) if err.kind() != ErrorKind::NotFound => return Err(err),
+                _ => (),
+            }
+        }
+        Ok(())
+    }
+
+    pub async fn file_metadata(&self, id: &str) -> Result<Metadata, IoError> {
+        self.with_file_path(id, fs::metadata).await
+    }
+
+    pub async fn available_space(&self) -> Result<u64, IoError> {
+        let path = self.path.clone();
+        tokio::task::spawn_blocking(move || fs2::available_space(path)).await?
+    }
+
+    // Lists files of both flat and sharded layouts, ignoring the staging area and thumbnails.
+    pub async fn list_files(&self) -> Result<Vec<StoredFile>, IoError> {
+        let mut files = Vec::new();
+        let mut dirs = vec![self.path.clone()];
+        while let Some(dir) = dirs.pop() {
+            let mut entries = fs::read_dir(&dir).await?;
+            while let Some(entry) = entries.next_entry().await? {
+                let metadata = entry.metadata().await?;
+                if metadata.is_dir() {
+                    if entry.path() != self.staging_path() {
+                        dirs.push(entry.path());
+                    }
+                    continue;
+                }
+                if !metadata.is_file() {
+                    continue;
+                }
+                let id = match entry.file_name().into_string() {
+                    Ok(id) if !id.ends_with(THUMBNAIL_SUFFIX) => id,
+                    _ => continue,
+                };
+                files.push(StoredFile {
+                    id,
+                    path: entry.path(),
+                    size: metadata.len(),
+                    modified: metadata.modified()?,
+                });
+            }
+        }
+        Ok(files)
+    }
+
+    // Moves files stored using another layout (e.g. flat) to their expected location.
+    // Failing files stay readable from their current location and are retried on next startup.
+    pub async fn migrate_layout(&self) -> Result<usize, IoError> {
+        let mut moved = 0;
+        for file in self.list_files().await? {
+            if !self.is_blob(&file) {
+                continue;
+            }
+            let path = self.file_path(&file.id);
+            if file.path == path {
+                continue;
+            }
+            if let Err(err) = self.move_file(&file.path, &path).await {
+                log::error!(
+                    "Cannot move file {} to {}: {}",
+                    file.path.display(),
+                    path.display(),
+                    err
+                );
+                continue;
+            }
+            // Thumbnails can be generated again, so one that cannot follow its file is dropped.
+            let thumbnail = file.thumbnail_path();
+            if let Err(err) = self
+                .move_file(&thumbnail, &self.thumbnail_path(&file.id))
+                .await
+            {
+                if err.kind() != ErrorKind::NotFound {
+                    log::warn!("Cannot move thumbnail {}: {}", thumbnail.display(), err);
+                    let _ = fs::remove_file(&thumbnail).await;
+                }
+            }
+            moved += 1;
+        }
+        Ok(moved)
+    }
+
+    async fn move_file(&self, from: &Path, to: &Path) -> Result<(), IoError> {
+        if let Some(parent) = to.parent() {
+            fs::create_dir_all(parent).await?;
+        }
+        fs::rename(from, to).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{path::PathBuf, time::SystemTime};
+
+    use super::{Dir, StoredFile};
+
+    #[test]
+    fn file_path() {
+        let id = "abcd1234-5678-90ab-cdef-1234567890ab";
+        assert_eq!(
+            Dir::new("uploads", 0).file_path(id),
+            PathBuf::from("uploads").join(id)
+        );
+        assert_eq!(
+            Dir::new("uploads", 2).file_path(id),
+            PathBuf::from("uploads/ab/cd").join(id)
+        );
+        assert_eq!(
+            Dir::new("uploads", 4).file_path(id),
+            PathBuf::from("uploads/ab/cd/12/34").join(id)
+        );
+    }
+
+    #[test]
+    fn is_blob() {
+        let dir = Dir::new("uploads", 2);
+        let stored = |path: &str| {
+            let path = PathBuf::from(path);
+            StoredFile {
+                id: path.file_name().unwrap().to_str().unwrap().to_owned(),
+                path,
+                size: 0,
+                modified: SystemTime::now(),
+            }
+        };
+        assert!(dir.is_blob(&stored(
+            "uploads/ab/cd/abcd1234-5678-90ab-cdef-1234567890ab"
+        )));
+        assert!(dir.is_blob(&stored("uploads/abcd1234-5678-90ab-cdef-1234567890ab")));
+        assert!(!dir.is_blob(&stored(
+            "uploads/lost+found/abcd1234-5678-90ab-cdef-1234567890ab"
+        )));
+        assert!(!dir.is_blob(&stored("uploads/ABCD1234-5678-90AB-CDEF-1234567890AB")));
+        assert!(!dir.is_blob(&stored("uploads/.DS_Store")));
+        assert!(!dir.is_blob(&stored("uploads/dropit.db")));
+    }
+
+    #[test]
+    fn thumbnail_path() {
+        let id = "abcd1234-5678-90ab-cdef-1234567890ab";
+        let file = StoredFile {
+            id: id.to_owned(),
+            path: PathBuf::from("uploads").join(id),
+            size: 0,
+            modified: SystemTime::now(),
+        };
+        assert_eq!(
+            file.thumbnail_path(),
+            PathBuf::from("uploads").join(format!("{}.thumb", id))
+        );
+        assert_eq!(
+            Dir::new("uploads", 2).thumbnail_path(id),
+            PathBuf::from("uploads/ab/cd").join(format!("{}.thumb", id))
+        );
     }
 }
@@ -1,5 +1,11 @@
 mod clean;
+mod compression;
 mod dir;
+mod reconcile;
+mod schedule;
 
 pub use clean::Cleaner;
+pub use compression::Compression;
 pub use dir::Dir;
+pub use reconcile::{Policy as ReconciliationPolicy, Reconciler};
+pub use schedule::Scheduler;
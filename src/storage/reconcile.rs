@@ -0,0 +1,212 @@
+use std::{
+    collections::HashMap,
+    io::ErrorKind,
+    str::FromStr,
+    time::{Duration, SystemTime},
+};
+
+use sqlx::{FromRow, SqlitePool};
+
+use crate::{
+    include_query,
+    storage::dir::{Dir, StoredFile},
+};
+
+// Files modified this recently may still be receiving an upload body.
+const WRITE_GRACE_PERIOD: Duration = Duration::from_secs(10 * 60);
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Policy {
+    Report,
+    Repair,
+}
+
+impl FromStr for Policy {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "report" => Ok(Self::Report),
+            "repair" => Ok(Self::Repair),
+            _ => Err("invalid policy (must be report or repair)"),
+        }
+    }
+}
+
+#[derive(FromRow)]
+struct Row {
+    id: String,
+    size: i64,
+    // Complete or trashed, whose blobs are kept until purged.
+    stored: bool,
+    compressed: bool,
+}
+
+#[derive(Default, Debug)]
+struct Summary {
+    orphan_blobs: usize,
+    foreign_files: usize,
+    dangling_rows: usize,
+    size_mismatches: usize,
+    repaired: usize,
+}
+
+pub struct Reconciler {
+    dir: Dir,
+    pool: SqlitePool,
+    policy: Policy,
+}
+
+impl Reconciler {
+    pub fn new(dir: Dir, pool: SqlitePool, policy: Policy) -> Self {
+        Self { dir, pool, policy }
+    }
+
+    pub async fn start(&self, interval: Duration) {
+        if interval.is_zero() {
+            return;
+        }
+        loop {
+            tokio::time::sleep(interval).await;
+            self.reconcile().await;
+        }
+    }
+
+    // The directory walk can be long, so the connection is only taken for each query,
+    // leaving uploads and downloads free to go on meanwhile.
+    pub async fn reconcile(&self) {
+        let stored = match self.dir.list_files().await {
+            Ok(files) => files,
+            Err(err) => {
+                log::error!("Cannot list files of the uploads directory: {}", err);
+                return;
+            }
+        };
+        let mut rows = match sqlx::query_as::<_, Row>(include_query!("get_files"))
+            .fetch_all(&self.pool)
+            .await
+        {
+            Ok(rows) => rows
+                .into_iter()
+                .map(|row| (row.id.clone(), row))
+                .collect::<HashMap<_, _>>(),
+            Err(err) => {
+                log::error!("Cannot fetch files for reconciliation: {:?}", err);
+                return;
+            }
+        };
+
+        let mut summary = Summary::default();
+        for file in stored {
+            // Files the app didn't create are never touched.
+            if !self.dir.is_blob(&file) {
+                summary.foreign_files += 1;
+                log::warn!(
+                    "Unexpected file {} in uploads directory, left untouched",
+                    file.path.display()
+                );
+                continue;
+            }
+            match rows.remove(&file.id) {
+                None => {
+                    summary.orphan_blobs += 1;
+                    log::warn!("File with id {} has no matching database entry", file.id);
+                    if self.policy == Policy::Repair && self.delete_blob(&file).await {
+                        summary.repaired += 1;
+                    }
+                }
+                // Compressed files are smaller on disk than their advertised size.
+                Some(row) if row.stored && !row.compressed && row.size as u64 != file.size => {
+                    let recently_modified = SystemTime::now()
+                        .duration_since(file.modified)
+                        .map(|elapsed| elapsed < WRITE_GRACE_PERIOD)
+                        .unwrap_or(true);
+                    if recently_modified {
+                        continue;
+                    }
+                    summary.size_mismatches += 1;
+                    log::warn!(
+                        "File with id {} has a size of {} bytes but {} were expected",
+                        file.id,
+                        file.size,
+                        row.size
+                    );
+                    if self.policy == Policy::Repair
+                        && self.delete_blob(&file).await
+                        && self.delete_row(&file.id).await
+                    {
+                        summary.repaired += 1;
+                    }
+                }
+                Some(_) => (),
+            }
+        }
+
+        // Remaining rows have no blob, unless one was committed since the directory listing.
+        // Pending uploads are still being written to the staging directory.
+        for (id, row) in rows {
+            if !row.stored {
+                continue;
+            }
+            match self.dir.file_metadata(&id).await {
+                Err(err) if err.kind() == ErrorKind::NotFound => (),
+                _ => continue,
+            }
+            summary.dangling_rows += 1;
+            log::warn!("File with id {} is absent from storage directory", id);
+            if self.policy == Policy::Repair && self.delete_row(&id).await {
+                summary.repaired += 1;
+            }
+        }
+
+        log::info!(
+            "Storage reconciliation found {} orphan file(s), {} unexpected file(s), {} dangling entry(ies) and {} size mismatch(es), {} repaired",
+            summary.orphan_blobs,
+            summary.foreign_files,
+            summary.dangling_rows,
+            summary.size_mismatches,
+            summary.repaired
+        );
+    }
+
+    // Removes the listed path itself, as the blob may not be stored where its id points to.
+    async fn delete_blob(&self, file: &StoredFile) -> bool {
+        match self.dir.delete_stored(file).await {
+            Ok(_) => true,
+            Err(err) => {
+                log::error!(
+                    "Cannot remove file {} from file system: {}",
+                    file.path.display(),
+                    err
+                );
+                false
+            }
+        }
+    }
+
+    async fn delete_row(&self, id: &str) -> bool {
+        match sqlx::query(include_query!("delete_file"))
+            .bind(id)
+            .execute(&self.pool)
+            .await
+        {
+            Ok(_) => true,
+            Err(err) => {
+                log::error!("Cannot remove file with id {} from database: {}", id, err);
+                false
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Policy;
+
+    #[test]
+    fn parse_policy() {
+        assert_eq!("report".parse::<Policy>(), Ok(Policy::Report));
+        assert_eq!("repair".parse::<Policy>(), Ok(Policy::Repair));
+        assert!("fix".parse::<Policy>().is_err());
+    }
+}
@@ -0,0 +1,111 @@
+use std::{collections::BTreeSet, sync::Mutex, time::Duration};
+
+use tokio::sync::Notify;
+
+// Upcoming deadlines kept in memory, later ones are loaded from the database as they get closer.
+pub(super) const SCHEDULE_CAPACITY: usize = 1024;
+
+// Earliest known expiration timestamps, waking the cleaner up when the first one is reached.
+// Deadlines are only hints: expired entries are always selected from the database,
+// so a deadline left behind by an extension or a removal only triggers an empty sweep.
+#[derive(Default)]
+pub struct Scheduler {
+    deadlines: Mutex<BTreeSet<u64>>,
+    notify: Notify,
+}
+
+impl Scheduler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Called after every change that may bring an expiration closer (uploads, extensions, etc.).
+    pub fn schedule(&self, timestamp: u64) {
+        let mut deadlines = self.deadlines.lock().unwrap();
+        if deadlines.insert(timestamp) {
+            truncate(&mut deadlines);
+            self.notify.notify_one();
+        }
+    }
+
+    // Replaces deadlines handled by a sweep at `swept` with the next ones from the database.
+    pub(super) fn reload(&self, swept: u64, upcoming: impl IntoIterator<Item = u64>) {
+        let mut deadlines = self.deadlines.lock().unwrap();
+        *deadlines = deadlines.split_off(&swept);
+        deadlines.extend(upcoming);
+        truncate(&mut deadlines);
+    }
+
+    // Time to wait before the next sweep, bounded by the periodic full rescan.
+    pub(super) fn wait(&self, now: u64, max: Duration) -> Duration {
+        match self.deadlines.lock().unwrap().iter().next() {
+            // Expired entries are strictly before the sweep timestamp.
+            Some(&deadline) if deadline < now => Duration::ZERO,
+            Some(&deadline) => Duration::from_secs(deadline - now + 1).min(max),
+            None => max,
+        }
+    }
+
+    pub(super) async fn notified(&self) {
+        self.notify.notified().await
+    }
+}
+
+// Later deadlines are dropped, they will be reloaded once the earlier ones are swept.
+fn truncate(deadlines: &mut BTreeSet<u64>) {
+    if let Some(&limit) = deadlines.iter().nth(SCHEDULE_CAPACITY) {
+        deadlines.split_off(&limit);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{Scheduler, SCHEDULE_CAPACITY};
+
+    const RESCAN: Duration = Duration::from_secs(3600);
+
+    #[test]
+    fn wait() {
+        let scheduler = Scheduler::new();
+        assert_eq!(scheduler.wait(100, RESCAN), RESCAN);
+        scheduler.schedule(160);
+        assert_eq!(scheduler.wait(100, RESCAN), Duration::from_secs(61));
+        scheduler.schedule(90);
+        assert_eq!(scheduler.wait(100, RESCAN), Duration::ZERO);
+        scheduler.schedule(100_000);
+        scheduler.reload(100, []);
+        assert_eq!(scheduler.wait(100, RESCAN), Duration::from_secs(61));
+    }
+
+    #[test]
+    fn reload() {
+        let scheduler = Scheduler::new();
+        scheduler.schedule(50);
+        scheduler.schedule(150);
+        scheduler.reload(100, [120, 150]);
+        assert_eq!(
+            scheduler
+                .deadlines
+                .lock()
+                .unwrap()
+                .iter()
+                .collect::<Vec<_>>(),
+            [&120, &150]
+        );
+    }
+
+    #[test]
+    fn capacity() {
+        let scheduler = Scheduler::new();
+        scheduler.reload(0, (1..=SCHEDULE_CAPACITY as u64 * 2).rev());
+        scheduler.schedule(0);
+        let deadlines = scheduler.deadlines.lock().unwrap();
+        assert_eq!(deadlines.len(), SCHEDULE_CAPACITY);
+        assert_eq!(
+            deadlines.iter().next_back(),
+            Some(&(SCHEDULE_CAPACITY as u64 - 1))
+        );
+    }
+}
@@ -1,6 +1,6 @@
 use axum::Extension;
 use http_negotiator::{ContentTypeNegotiation, Negotiation};
-use sqlx::SqlitePool;
+use sqlx::{SqliteConnection, SqlitePool};
 
 use crate::{
     alias,
@@ -8,18 +8,18 @@ use crate::{
     error::{alias as AliasError, Error},
     include_query,
     response::{ApiResponse, ResponseType},
-    update::{alias::AliasChange, AdminToken},
+    update::{alias::AliasChange, AdminAccess},
     upload::DomainUri,
 };
 
 pub async fn handler(
     Extension(pool): Extension<SqlitePool>,
     alias: Alias,
-    AdminToken(admin_token): AdminToken,
+    access: AdminAccess,
     DomainUri(domain_uri): DomainUri,
     response_type: Negotiation<ContentTypeNegotiation, ResponseType>,
 ) -> Result<ApiResponse<AliasChange>, Error> {
-    let (new_short, new_long) = process_change(pool, alias, admin_token).await?;
+    let (new_short, new_long) = process_change(pool, alias, access).await?;
     Ok(ApiResponse(
         response_type.into_inner(),
         AliasChange {
@@ -32,18 +32,25 @@ pub async fn handler(
 async fn process_change(
     pool: SqlitePool,
     alias: Alias,
-    admin_token: String,
+    access: AdminAccess,
 ) -> Result<(String, String), Error> {
-    let (id, _size, mut conn) = super::super::authorize(pool, &alias, &admin_token).await?;
-    let (short, long) = alias::random_unused_aliases(&mut conn)
+    let (id, _size, mut conn) = super::super::authorize(pool, &alias, &access).await?;
+    regenerate(&mut conn, &id).await
+}
+
+pub(in crate::update) async fn regenerate(
+    conn: &mut SqliteConnection,
+    id: &str,
+) -> Result<(String, String), Error> {
+    let (short, long) = alias::random_unused_aliases(conn)
         .await
         .ok_or(AliasError::AliasGeneration)?;
 
     let affected = sqlx::query(include_query!("update_file_aliases"))
         .bind(&short)
         .bind(&long)
-        .bind(&id)
-        .execute(&mut conn)
+        .bind(id)
+        .execute(conn)
         .await
         .map_err(|_| AliasError::Database)?
         .rows_affected();
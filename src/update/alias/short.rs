@@ -8,18 +8,18 @@ use crate::{
     error::{alias as AliasError, Error},
     include_query,
     response::{ApiResponse, ResponseType},
-    update::{alias::AliasChange, AdminToken},
+    update::{alias::AliasChange, AdminAccess},
     upload::DomainUri,
 };
 
 pub async fn handler(
     Extension(pool): Extension<SqlitePool>,
     alias: Alias,
-    AdminToken(admin_token): AdminToken,
+    access: AdminAccess,
     DomainUri(domain_uri): DomainUri,
     response_type: Negotiation<ContentTypeNegotiation, ResponseType>,
 ) -> Result<ApiResponse<AliasChange>, Error> {
-    let new_alias = process_change(pool, alias, admin_token).await?;
+    let new_alias = process_change(pool, alias, access).await?;
     Ok(ApiResponse(
         response_type.into_inner(),
         AliasChange {
@@ -32,9 +32,9 @@ pub async fn handler(
 async fn process_change(
     pool: SqlitePool,
     alias: Alias,
-    admin_token: String,
+    access: AdminAccess,
 ) -> Result<String, Error> {
-    let (id, _size, mut conn) = super::super::authorize(pool, &alias, &admin_token).await?;
+    let (id, _size, mut conn) = super::super::authorize(pool, &alias, &access).await?;
     let alias = alias::random_unused_short(&mut conn)
         .await
         .ok_or(AliasError::AliasGeneration)?;
@@ -0,0 +1,183 @@
+use std::sync::Arc;
+
+use axum::{Extension, Json};
+use http_negotiator::{ContentTypeNegotiation, Negotiation};
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+use sqlx::{SqliteConnection, SqlitePool};
+
+use crate::{
+    alias::Alias,
+    error::{admin as AdminError, Error},
+    response::{ApiHeader, ApiResponse, ResponseType, SingleLine},
+    storage::{Cleaner, Scheduler},
+    update::{alias::AliasChange, AdminAccess, DurationRequest},
+    upload::{Determiner, DomainUri, Expiration},
+};
+
+// Every file is processed using the same pooled connection.
+const BATCH_MAX_FILES: usize = 100;
+
+#[derive(Deserialize)]
+pub struct BatchRequest {
+    operation: Operation,
+    files: Vec<BatchFile>,
+}
+
+// Same shape as collection members. Files without admin token use the caller's session.
+#[derive(Deserialize)]
+struct BatchFile {
+    alias: Alias,
+    admin: Option<String>,
+}
+
+#[derive(Deserialize, Copy, Clone)]
+#[serde(tag = "type", rename_all = "lowercase")]
+enum Operation {
+    Extend { duration: DurationRequest },
+    Revoke,
+    Alias,
+    Downloads { count: u16 },
+    Inactivity { period: u64 },
+}
+
+#[derive(Serialize)]
+pub struct BatchResult {
+    results: Vec<ItemResult>,
+}
+
+#[derive(Serialize)]
+struct ItemResult {
+    alias: String,
+    success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    expiration: Option<Expiration>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    aliases: Option<AliasChange>,
+}
+
+impl ItemResult {
+    fn new(alias: &Alias, result: Result<Outcome, Error>) -> Self {
+        let mut item = Self {
+            alias: alias.inner().to_owned(),
+            success: result.is_ok(),
+            error: None,
+            expiration: None,
+            aliases: None,
+        };
+        match result {
+            Ok(Outcome::Done) => (),
+            Ok(Outcome::Extended(expiration)) => item.expiration = Some(expiration),
+            Ok(Outcome::Aliased(aliases)) => item.aliases = Some(aliases),
+            Err(err) => item.error = Some(err.to_string()),
+        }
+        item
+    }
+}
+
+enum Outcome {
+    Done,
+    Extended(Expiration),
+    Aliased(AliasChange),
+}
+
+impl ApiHeader for BatchResult {}
+
+impl SingleLine for BatchResult {
+    fn single_lined(&self) -> String {
+        self.results
+            .iter()
+            .map(|item| match &item.error {
+                Some(err) => format!("{} {}", item.alias, err),
+                None => format!("{} ok", item.alias),
+            })
+            .join("\n")
+    }
+}
+
+// Applies one operation to many files. Each file succeeds or fails on its own.
+#[allow(clippy::too_many_arguments)]
+pub async fn handler(
+    Extension(pool): Extension<SqlitePool>,
+    response_type: Negotiation<ContentTypeNegotiation, ResponseType>,
+    session: Option<AdminAccess>,
+    Extension(determiner): Extension<Arc<Determiner>>,
+    Extension(cleaner): Extension<Arc<Cleaner>>,
+    Extension(scheduler): Extension<Arc<Scheduler>>,
+    DomainUri(domain_uri): DomainUri,
+    Json(request): Json<BatchRequest>,
+) -> Result<ApiResponse<BatchResult>, ApiResponse<Error>> {
+    if request.files.len() > BATCH_MAX_FILES {
+        return Err(ApiResponse(*response_type, AdminError::BatchTooLarge));
+    }
+    let mut conn = pool
+        .acquire()
+        .await
+        .map_err(|_| ApiResponse(*response_type, AdminError::Database))?;
+    let mut results = Vec::with_capacity(request.files.len());
+    for file in request.files {
+        let access = match (file.admin, &session) {
+            (Some(token), _) => Ok(AdminAccess::Token(token)),
+            (None, Some(AdminAccess::Owner(username))) => Ok(AdminAccess::Owner(username.clone())),
+            (None, Some(AdminAccess::Administrator(username))) => {
+                Ok(AdminAccess::Administrator(username.clone()))
+            }
+            (None, _) => Err(AdminError::InvalidAuthorizationHeader),
+        };
+        let result = match access {
+            Ok(access) => {
+                apply(
+                    &mut conn,
+                    &file.alias,
+                    &access,
+                    request.operation,
+                    &determiner,
+                    &cleaner,
+                    &scheduler,
+                    &domain_uri,
+                )
+                .await
+            }
+            Err(err) => Err(err),
+        };
+        results.push(ItemResult::new(&file.alias, result));
+    }
+    Ok(ApiResponse(*response_type, BatchResult { results }))
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn apply(
+    conn: &mut SqliteConnection,
+    alias: &Alias,
+    access: &AdminAccess,
+    operation: Operation,
+    determiner: &Determiner,
+    cleaner: &Cleaner,
+    scheduler: &Scheduler,
+    domain_uri: &str,
+) -> Result<Outcome, Error> {
+    let (id, size) = super::authorize_file(conn, alias, access).await?;
+    match operation {
+        Operation::Extend { duration } => {
+            super::expiration::extend(conn, determiner, scheduler, &id, size, duration, access)
+                .await
+                .map(Outcome::Extended)
+        }
+        Operation::Revoke => cleaner.remove_file(&id, conn).await.map(|_| Outcome::Done),
+        Operation::Alias => {
+            let (short, long) = super::alias::both::regenerate(conn, &id).await?;
+            Ok(Outcome::Aliased(AliasChange {
+                short: Some((short.clone(), format!("{}/{}", domain_uri, short))),
+                long: Some((long.clone(), format!("{}/{}", domain_uri, long))),
+            }))
+        }
+        Operation::Downloads { count } => super::downloads::limit(conn, &id, count)
+            .await
+            .map(|_| Outcome::Done),
+        Operation::Inactivity { period } => super::inactivity::limit(conn, scheduler, &id, period)
+            .await
+            .map(|_| Outcome::Done),
+    }
+}
@@ -0,0 +1,50 @@
+use axum::Extension;
+use http_negotiator::{ContentTypeNegotiation, Negotiation};
+use sqlx::SqlitePool;
+
+use crate::{
+    alias::Alias,
+    error::{details as DetailsError, Error},
+    include_query,
+    response::{ApiResponse, ResponseType},
+    update::AdminAccess,
+    upload::Details,
+};
+
+// Only the given X-Description and X-Tags headers are updated.
+pub async fn handler(
+    Extension(pool): Extension<SqlitePool>,
+    response_type: Negotiation<ContentTypeNegotiation, ResponseType>,
+    access: AdminAccess,
+    alias: Alias,
+    details: Details,
+) -> Result<ApiResponse<()>, ApiResponse<Error>> {
+    process_details(pool, alias, access, details)
+        .await
+        .map_err(|err| ApiResponse(*response_type, err))?;
+    Ok(ApiResponse(*response_type, ()))
+}
+
+async fn process_details(
+    pool: SqlitePool,
+    alias: Alias,
+    access: AdminAccess,
+    details: Details,
+) -> Result<(), Error> {
+    if details.is_empty() {
+        return Err(DetailsError::DetailsHeader);
+    }
+    let (id, _size, mut conn) = super::authorize(pool, &alias, &access).await?;
+
+    sqlx::query(include_query!("update_file_details"))
+        .bind(details.description.is_some())
+        .bind(details.description_column())
+        .bind(details.tags.is_some())
+        .bind(details.tags_column())
+        .bind(&id)
+        .execute(&mut conn)
+        .await
+        .map_err(|_| DetailsError::UnexpectedFileModification)?;
+
+    Ok(())
+}
@@ -1,23 +1,23 @@
 use axum::{extract::Path, Extension};
 use http_negotiator::{ContentTypeNegotiation, Negotiation};
-use sqlx::SqlitePool;
+use sqlx::{SqliteConnection, SqlitePool};
 
 use crate::{
     alias::Alias,
     error::{downloads as DownloadsError, Error},
     include_query,
     response::{ApiResponse, ResponseType},
-    update::AdminToken,
+    update::AdminAccess,
 };
 
 pub async fn handler(
     Extension(pool): Extension<SqlitePool>,
     response_type: Negotiation<ContentTypeNegotiation, ResponseType>,
-    AdminToken(admin_token): AdminToken,
+    access: AdminAccess,
     alias: Alias,
     Path((_, count)): Path<(String, u16)>,
 ) -> Result<ApiResponse<()>, ApiResponse<Error>> {
-    process_downloads(pool, alias, admin_token, count)
+    process_downloads(pool, alias, access, count)
         .await
         .map_err(|err| ApiResponse(*response_type, err))?;
     Ok(ApiResponse(*response_type, ()))
@@ -26,16 +26,21 @@ pub async fn handler(
 async fn process_downloads(
     pool: SqlitePool,
     alias: Alias,
-    admin_token: String,
+    access: AdminAccess,
     count: u16,
 ) -> Result<(), Error> {
-    let (id, _size, mut conn) = super::authorize(pool, &alias, &admin_token).await?;
+    let (id, _size, mut conn) = super::authorize(pool, &alias, &access).await?;
+    limit(&mut conn, &id, count).await
+}
+
+// A count of 0 removes the limit.
+pub(super) async fn limit(conn: &mut SqliteConnection, id: &str, count: u16) -> Result<(), Error> {
     let count = if count >= 1 { Some(count) } else { None };
 
     sqlx::query(include_query!("update_file_downloads"))
         .bind(count)
-        .bind(&id)
-        .execute(&mut conn)
+        .bind(id)
+        .execute(conn)
         .await
         .map_err(|_| DownloadsError::UnexpectedFileModification)?;
 
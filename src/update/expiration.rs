@@ -1,16 +1,21 @@
-use std::{convert::TryFrom, sync::Arc, time::Duration};
+use std::{
+    convert::TryFrom,
+    sync::Arc,
+    time::{Duration, SystemTime},
+};
 
 use axum::{extract::Path, Extension};
 use http_negotiator::{ContentTypeNegotiation, Negotiation};
 use serde::{de::Unexpected, Deserialize, Deserializer};
-use sqlx::SqlitePool;
+use sqlx::{SqliteConnection, SqlitePool};
 
 use crate::{
     alias::Alias,
     error::{expiration as ExpirationError, Error},
     include_query,
     response::{ApiResponse, ResponseType},
-    update::AdminToken,
+    storage::Scheduler,
+    update::AdminAccess,
     upload::{Determiner, Expiration},
 };
 
@@ -26,13 +31,29 @@ impl<'de> Deserialize<'de> for DurationRequest {
     where
         D: Deserializer<'de>,
     {
-        let input = <&str>::deserialize(deserializer)?;
+        // Owned, as batch requests deserialize it from buffered content.
+        let input = String::deserialize(deserializer)?;
+        Self::parse(&input, SystemTime::now())
+            .map_err(|err| serde::de::Error::invalid_value(Unexpected::Str(&input), &err.as_str()))
+    }
+}
+
+impl DurationRequest {
+    // Absolute UTC dates (2024-06-21T18:00:00Z) are turned into the custom duration left until
+    // them, so they are capped like any other duration.
+    fn parse(input: &str, now: SystemTime) -> Result<Self, String> {
         match input {
             "init" | "initial" => Ok(Self::Initial),
             "max" | "maximum" => Ok(Self::Maximum),
-            _ => input.parse().map(Self::Custom).map_err(|err| {
-                serde::de::Error::invalid_value(Unexpected::Str(input), &err.to_string().as_str())
-            }),
+            _ if input.contains('-') => humantime::parse_rfc3339_weak(input)
+                .map_err(|err| err.to_string())?
+                .duration_since(now)
+                .map(|duration| Self::Custom(duration.as_secs()))
+                .map_err(|_| "a date in the future".to_owned()),
+            _ => input
+                .parse()
+                .map(Self::Custom)
+                .map_err(|err| err.to_string()),
         }
     }
 }
@@ -41,13 +62,14 @@ pub async fn handler(
     Extension(pool): Extension<SqlitePool>,
     response_type: Negotiation<ContentTypeNegotiation, ResponseType>,
     Extension(determiner): Extension<Arc<Determiner>>,
-    AdminToken(admin_token): AdminToken,
+    Extension(scheduler): Extension<Arc<Scheduler>>,
+    access: AdminAccess,
     alias: Alias,
     Path((_, duration)): Path<(String, DurationRequest)>,
 ) -> Result<ApiResponse<Expiration>, ApiResponse<Error>> {
     Ok(ApiResponse(
         *response_type,
-        process_extend(pool, determiner, alias, duration, admin_token)
+        process_extend(pool, determiner, &scheduler, alias, duration, access)
             .await
             .map_err(|err| ApiResponse(*response_type, err))?,
     ))
@@ -56,39 +78,99 @@ pub async fn handler(
 async fn process_extend(
     pool: SqlitePool,
     determiner: Arc<Determiner>,
+    scheduler: &Scheduler,
     alias: Alias,
     duration: DurationRequest,
-    admin_token: String,
+    access: AdminAccess,
 ) -> Result<Expiration, Error> {
-    let (id, size, mut conn) = super::authorize(pool, &alias, &admin_token).await?;
+    let (id, size, mut conn) = super::authorize(pool, &alias, &access).await?;
+    extend(
+        &mut conn,
+        &determiner,
+        scheduler,
+        &id,
+        size,
+        duration,
+        &access,
+    )
+    .await
+}
 
-    let (default, allowed) = determiner
-        .determine(size)
-        .ok_or(ExpirationError::TooLarge)?;
-    let expiration = Expiration::try_from(match duration {
-        DurationRequest::Initial => default,
-        DurationRequest::Maximum => {
-            if let Some(duration) = allowed {
-                duration
-            } else {
-                return Err(ExpirationError::ExpirationTooHigh);
-            }
-        }
-        DurationRequest::Custom(secs) => {
-            let dur = Duration::from_secs(secs);
-            if dur > allowed.unwrap_or(default) {
-                return Err(ExpirationError::ExpirationTooHigh);
+pub(super) async fn extend(
+    conn: &mut SqliteConnection,
+    determiner: &Determiner,
+    scheduler: &Scheduler,
+    id: &str,
+    size: u64,
+    duration: DurationRequest,
+    access: &AdminAccess,
+) -> Result<Expiration, Error> {
+    // Server administrators may set any custom duration, even on files above every threshold.
+    let duration = match (duration, access) {
+        (DurationRequest::Custom(secs), AdminAccess::Administrator(_)) => Duration::from_secs(secs),
+        (duration, _) => {
+            let (default, allowed) = determiner
+                .determine(size)
+                .ok_or(ExpirationError::TooLarge)?;
+            match duration {
+                DurationRequest::Initial => default,
+                DurationRequest::Maximum => {
+                    if let Some(duration) = allowed {
+                        duration
+                    } else {
+                        return Err(ExpirationError::ExpirationTooHigh);
+                    }
+                }
+                DurationRequest::Custom(secs) => {
+                    let dur = Duration::from_secs(secs);
+                    if dur > allowed.unwrap_or(default) {
+                        return Err(ExpirationError::ExpirationTooHigh);
+                    }
+                    dur
+                }
             }
-            dur
         }
-    })?;
+    };
+    let expiration = Expiration::try_from(duration)?;
 
     sqlx::query(include_query!("extend_file"))
         .bind(expiration.timestamp() as i64)
         .bind(id)
-        .execute(&mut conn)
+        .execute(conn)
         .await
         .map_err(|_| ExpirationError::Database)?;
+    scheduler.schedule(expiration.timestamp());
 
     Ok(expiration)
 }
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, UNIX_EPOCH};
+
+    use super::DurationRequest;
+
+    #[test]
+    fn parse() {
+        // 2024-06-21T12:00:00Z
+        let now = UNIX_EPOCH + Duration::from_secs(1_718_971_200);
+        assert!(matches!(
+            DurationRequest::parse("max", now),
+            Ok(DurationRequest::Maximum)
+        ));
+        assert!(matches!(
+            DurationRequest::parse("3600", now),
+            Ok(DurationRequest::Custom(3600))
+        ));
+        assert!(matches!(
+            DurationRequest::parse("2024-06-21T18:00:00Z", now),
+            Ok(DurationRequest::Custom(21600))
+        ));
+        assert!(matches!(
+            DurationRequest::parse("2024-06-21 18:00:00", now),
+            Ok(DurationRequest::Custom(21600))
+        ));
+        assert!(DurationRequest::parse("2024-06-20T18:00:00Z", now).is_err());
+        assert!(DurationRequest::parse("friday", now).is_err());
+    }
+}
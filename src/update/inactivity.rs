@@ -0,0 +1,83 @@
+use std::{
+    sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use axum::{extract::Path, Extension};
+use http_negotiator::{ContentTypeNegotiation, Negotiation};
+use sqlx::{SqliteConnection, SqlitePool};
+
+use crate::{
+    alias::Alias,
+    error::{inactivity as InactivityError, Error},
+    include_query,
+    response::{ApiResponse, ResponseType},
+    storage::Scheduler,
+    update::AdminAccess,
+    upload::MAX_TIMESTAMP,
+};
+
+pub async fn handler(
+    Extension(pool): Extension<SqlitePool>,
+    response_type: Negotiation<ContentTypeNegotiation, ResponseType>,
+    Extension(scheduler): Extension<Arc<Scheduler>>,
+    access: AdminAccess,
+    alias: Alias,
+    Path((_, period)): Path<(String, u64)>,
+) -> Result<ApiResponse<()>, ApiResponse<Error>> {
+    process_inactivity(pool, &scheduler, alias, access, period)
+        .await
+        .map_err(|err| ApiResponse(*response_type, err))?;
+    Ok(ApiResponse(*response_type, ()))
+}
+
+async fn process_inactivity(
+    pool: SqlitePool,
+    scheduler: &Scheduler,
+    alias: Alias,
+    access: AdminAccess,
+    period: u64,
+) -> Result<(), Error> {
+    let (id, _size, mut conn) = super::authorize(pool, &alias, &access).await?;
+    limit(&mut conn, scheduler, &id, period).await
+}
+
+// Files expire once left undownloaded for this many seconds, on top of their expiration date.
+// The period starts now, so enabling it never removes a file right away. A period of 0 disables it.
+pub(super) async fn limit(
+    conn: &mut SqliteConnection,
+    scheduler: &Scheduler,
+    id: &str,
+    period: u64,
+) -> Result<(), Error> {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_err(|_| InactivityError::TimeCalculation)?
+        .as_secs();
+    // Removals are scheduled at the end of the period, which must remain a storable date.
+    if now
+        .checked_add(period)
+        .filter(|&end| end <= MAX_TIMESTAMP)
+        .is_none()
+    {
+        return Err(InactivityError::ExpirationTooHigh);
+    }
+    let period = if period >= 1 {
+        Some(period as i64)
+    } else {
+        None
+    };
+
+    sqlx::query(include_query!("update_file_inactivity"))
+        .bind(period)
+        .bind(now as i64)
+        .bind(id)
+        .execute(conn)
+        .await
+        .map_err(|_| InactivityError::UnexpectedFileModification)?;
+    if let Some(period) = period {
+        scheduler.schedule(now + period as u64);
+    }
+
+    Ok(())
+}
@@ -3,48 +3,67 @@ use std::sync::Arc;
 use async_trait::async_trait;
 use axum::{
     extract::{FromRequest, RequestParts},
-    routing::{delete, patch},
-    Extension, Router,
+    headers::{authorization::Basic, Authorization, Cookie},
+    routing::{delete, patch, post},
+    Extension, Router, TypedHeader,
 };
-use hyper::{header, Body};
-use sqlx::{pool::PoolConnection, Sqlite, SqlitePool};
+use hyper::{header, http::HeaderValue, Body};
+use sqlx::{pool::PoolConnection, Sqlite, SqliteConnection, SqlitePool};
 
 use crate::{
     alias::Alias,
+    auth::{AuthStatus, Authenticator, Origin, Role},
     error::{admin as AdminError, Error},
     include_query,
-    storage::Dir,
+    storage::{Cleaner, Scheduler},
     upload::Determiner,
 };
 
 mod alias;
+mod batch;
+mod details;
 mod downloads;
 mod expiration;
+mod inactivity;
+mod name;
+mod restore;
 mod revoke;
+mod rotate;
+mod token;
+
+pub use expiration::DurationRequest;
+pub use token::{check_admin_token, hash_admin_token, new_admin_token};
 
 async fn authorize(
     pool: SqlitePool,
     alias: &Alias,
-    admin_token: &str,
+    access: &AdminAccess,
 ) -> Result<(String, u64, PoolConnection<Sqlite>), Error> {
     let mut conn = pool.acquire().await.map_err(|_| AdminError::Database)?;
+    let (id, size) = authorize_file(&mut conn, alias, access).await?;
+    Ok((id, size, conn))
+}
 
-    let (id, size, admin) =
-        sqlx::query_as::<_, (String, i64, String)>(include_query!("get_file_admin"))
+// Returns the id and size of the file.
+async fn authorize_file(
+    conn: &mut SqliteConnection,
+    alias: &Alias,
+    access: &AdminAccess,
+) -> Result<(String, u64), Error> {
+    let (id, size, admin, origin) =
+        sqlx::query_as::<_, (String, i64, String, String)>(include_query!("get_file_admin"))
             .bind(alias.inner())
             .bind(alias.inner())
-            .fetch_optional(&mut conn)
+            .fetch_optional(conn)
             .await
             .map_err(|_| AdminError::Database)?
             .ok_or(AdminError::FileNotFound)?;
 
-    if admin != admin_token.to_ascii_lowercase() {
-        return Err(AdminError::InvalidAdminToken);
-    }
-    Ok((id, size as u64, conn))
+    access.check(&admin, &origin)?;
+    Ok((id, size as u64))
 }
 
-pub struct AdminToken(String);
+pub struct AdminToken(pub String);
 
 #[async_trait]
 impl FromRequest<Body> for AdminToken {
@@ -63,15 +82,105 @@ impl FromRequest<Body> for AdminToken {
     }
 }
 
-pub fn router(pool: SqlitePool, dir: Dir, determiner: Arc<Determiner>) -> Router {
+// Files are managed using their admin token, by their uploader when origins are usernames,
+// or by any server administrator.
+pub enum AdminAccess {
+    Token(String),
+    Owner(String),
+    Administrator(String),
+}
+
+impl AdminAccess {
+    pub fn check(&self, admin: &str, origin: &str) -> Result<(), Error> {
+        match self {
+            Self::Token(token) => check_admin_token(admin, token),
+            Self::Owner(username) if username == origin => Ok(()),
+            Self::Owner(_) => Err(AdminError::InvalidAdminToken),
+            Self::Administrator(_) => Ok(()),
+        }
+    }
+}
+
+#[async_trait]
+impl FromRequest<Body> for AdminAccess {
+    type Rejection = Error;
+
+    async fn from_request(req: &mut RequestParts<Body>) -> Result<Self, Self::Rejection> {
+        // Basic credentials identify the uploader, any other authorization is an admin token.
+        let is_basic = |value: &HeaderValue| {
+            value
+                .to_str()
+                .map(|value| value.len() > 6 && value[..6].eq_ignore_ascii_case("basic "))
+                .unwrap_or(false)
+        };
+        if req.headers().contains_key("X-Authorization")
+            || req
+                .headers()
+                .get(header::AUTHORIZATION)
+                .map(|value| !is_basic(value))
+                .unwrap_or(false)
+        {
+            let AdminToken(token) = AdminToken::from_request(req).await?;
+            return Ok(Self::Token(token));
+        }
+
+        let is_owner = matches!(
+            Extension::<Origin>::from_request(req).await,
+            Ok(Extension(Origin::Username))
+        );
+        let Extension(authenticator) = Extension::<Arc<Authenticator>>::from_request(req)
+            .await
+            .map_err(|_| AdminError::InvalidAuthorizationHeader)?;
+        let auth_header = Option::<TypedHeader<Authorization<Basic>>>::from_request(req)
+            .await
+            .ok()
+            .flatten();
+        let cookie = Option::<TypedHeader<Cookie>>::from_request(req)
+            .await
+            .ok()
+            .flatten();
+        match authenticator
+            .identify(auth_header.map(|h| h.0), cookie.map(|h| h.0))
+            .await
+        {
+            AuthStatus::Valid(username) => match authenticator.role(&username).await {
+                Role::Administrator => Ok(Self::Administrator(username)),
+                Role::User if is_owner => Ok(Self::Owner(username)),
+                Role::User => Err(AdminError::InvalidAuthorizationHeader),
+            },
+            AuthStatus::Error(err) => Err(err),
+            AuthStatus::NotNeeded | AuthStatus::Prompt => {
+                Err(AdminError::InvalidAuthorizationHeader)
+            }
+        }
+    }
+}
+
+pub fn router(
+    pool: SqlitePool,
+    cleaner: Arc<Cleaner>,
+    scheduler: Arc<Scheduler>,
+    determiner: Arc<Determiner>,
+    authenticator: Arc<Authenticator>,
+    origin: Origin,
+) -> Router {
     Router::new()
         .route("/:alias/alias/short", patch(alias::short::handler))
         .route("/:alias/alias/long", patch(alias::long::handler))
         .route("/:alias/alias", patch(alias::both::handler))
         .route("/:alias/downloads/:count", patch(downloads::handler))
+        .route("/:alias/name", patch(name::handler))
+        .route("/:alias/details", patch(details::handler))
+        .route("/:alias/admin", patch(rotate::handler))
         .route("/:alias/expiration/:duration", patch(expiration::handler))
+        .route("/:alias/inactivity/:period", patch(inactivity::handler))
         .route("/:alias", delete(revoke::handler))
+        .route("/:alias/restore", patch(restore::handler))
+        .route("/batch", post(batch::handler))
         .route_layer(Extension(pool))
-        .route_layer(Extension(dir))
+        .route_layer(Extension(cleaner))
+        .route_layer(Extension(scheduler))
         .route_layer(Extension(determiner))
+        .route_layer(Extension(authenticator))
+        .route_layer(Extension(origin))
 }
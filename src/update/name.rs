@@ -0,0 +1,45 @@
+use axum::Extension;
+use http_negotiator::{ContentTypeNegotiation, Negotiation};
+use sqlx::SqlitePool;
+
+use crate::{
+    alias::Alias,
+    error::{details as DetailsError, Error},
+    include_query,
+    response::{ApiResponse, ResponseType},
+    update::AdminAccess,
+    upload::Filename,
+};
+
+// The new name is given like at upload, using the X-Filename header.
+pub async fn handler(
+    Extension(pool): Extension<SqlitePool>,
+    response_type: Negotiation<ContentTypeNegotiation, ResponseType>,
+    access: AdminAccess,
+    alias: Alias,
+    Filename(filename): Filename,
+) -> Result<ApiResponse<()>, ApiResponse<Error>> {
+    process_rename(pool, alias, access, filename)
+        .await
+        .map_err(|err| ApiResponse(*response_type, err))?;
+    Ok(ApiResponse(*response_type, ()))
+}
+
+async fn process_rename(
+    pool: SqlitePool,
+    alias: Alias,
+    access: AdminAccess,
+    filename: Option<String>,
+) -> Result<(), Error> {
+    let filename = filename.ok_or(DetailsError::FilenameHeader)?;
+    let (id, _size, mut conn) = super::authorize(pool, &alias, &access).await?;
+
+    sqlx::query(include_query!("update_file_name"))
+        .bind(filename)
+        .bind(&id)
+        .execute(&mut conn)
+        .await
+        .map_err(|_| DetailsError::UnexpectedFileModification)?;
+
+    Ok(())
+}
@@ -0,0 +1,109 @@
+use std::{
+    convert::TryFrom,
+    sync::Arc,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use axum::Extension;
+use http_negotiator::{ContentTypeNegotiation, Negotiation};
+use sqlx::{FromRow, SqlitePool};
+
+use crate::{
+    alias::Alias,
+    error::{restore as RestoreError, Error},
+    include_query,
+    response::{ApiResponse, ResponseType},
+    storage::Scheduler,
+    update::AdminAccess,
+    upload::{Determiner, Expiration},
+};
+
+#[derive(FromRow)]
+struct TrashedFile {
+    id: String,
+    size: i64,
+    admin: String,
+    origin: String,
+    expiration: i64,
+}
+
+// Brings back a file trashed during the grace period, under its previous aliases.
+pub async fn handler(
+    Extension(pool): Extension<SqlitePool>,
+    response_type: Negotiation<ContentTypeNegotiation, ResponseType>,
+    Extension(determiner): Extension<Arc<Determiner>>,
+    Extension(scheduler): Extension<Arc<Scheduler>>,
+    access: AdminAccess,
+    alias: Alias,
+) -> Result<ApiResponse<Expiration>, ApiResponse<Error>> {
+    process_restore(pool, &determiner, &scheduler, alias, access)
+        .await
+        .map(|expiration| ApiResponse(*response_type, expiration))
+        .map_err(|err| ApiResponse(*response_type, err))
+}
+
+async fn process_restore(
+    pool: SqlitePool,
+    determiner: &Determiner,
+    scheduler: &Scheduler,
+    alias: Alias,
+    access: AdminAccess,
+) -> Result<Expiration, Error> {
+    let mut conn = pool.acquire().await.map_err(|_| RestoreError::Database)?;
+    let file = sqlx::query_as::<_, TrashedFile>(include_query!("get_file_trashed"))
+        .bind(alias.inner())
+        .bind(alias.inner())
+        .fetch_optional(&mut conn)
+        .await
+        .map_err(|_| RestoreError::Database)?
+        .ok_or(RestoreError::FileNotFound)?;
+    access.check(&file.admin, &file.origin)?;
+
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_err(|_| RestoreError::TimeCalculation)?
+        .as_secs();
+    let (default, _) = determiner
+        .determine(file.size as u64)
+        .ok_or(RestoreError::TooLarge)?;
+    let expiration = Expiration::try_from(restored_duration(
+        Duration::from_secs((file.expiration.max(0) as u64).saturating_sub(now)),
+        default,
+    ))?;
+
+    // Restarting the inactivity period prevents an immediate removal as well.
+    sqlx::query(include_query!("restore_file"))
+        .bind(expiration.timestamp() as i64)
+        .bind(now as i64)
+        .bind(&file.id)
+        .execute(&mut conn)
+        .await
+        .map_err(|_| RestoreError::Database)?;
+    // An immediate sweep reloads both the expiration and inactivity deadlines of the file.
+    scheduler.schedule(now);
+
+    Ok(expiration)
+}
+
+// Files that expired in the meantime get the default duration of their size.
+fn restored_duration(remaining: Duration, default: Duration) -> Duration {
+    if remaining.is_zero() {
+        default
+    } else {
+        remaining
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::restored_duration;
+
+    #[test]
+    fn duration() {
+        let hours = |h: u64| Duration::from_secs(h * 3600);
+        assert_eq!(restored_duration(hours(2), hours(24)), hours(2));
+        assert_eq!(restored_duration(Duration::ZERO, hours(24)), hours(24));
+    }
+}
@@ -1,24 +1,26 @@
+use std::sync::Arc;
+
 use axum::Extension;
 use http_negotiator::{ContentTypeNegotiation, Negotiation};
 use sqlx::SqlitePool;
 
 use crate::{
-    alias::Alias,
+    alias::{Alias, AliasGroup},
     error::{revoke as RevokeError, Error},
-    include_query,
     response::{ApiResponse, ResponseType},
-    storage::Dir,
-    update::AdminToken,
+    storage::Cleaner,
+    update::AdminAccess,
 };
 
+// Multiple files can be revoked at once by joining their aliases with a '+'.
 pub async fn handler(
     Extension(pool): Extension<SqlitePool>,
     response_type: Negotiation<ContentTypeNegotiation, ResponseType>,
-    AdminToken(admin_token): AdminToken,
-    alias: Alias,
-    Extension(dir): Extension<Dir>,
+    access: AdminAccess,
+    AliasGroup(aliases): AliasGroup,
+    Extension(cleaner): Extension<Arc<Cleaner>>,
 ) -> Result<ApiResponse<()>, ApiResponse<Error>> {
-    process_revoke(pool, alias, admin_token, dir)
+    process_revoke(pool, aliases, access, &cleaner)
         .await
         .map_err(|err| ApiResponse(*response_type, err))?;
     Ok(ApiResponse(*response_type, ()))
@@ -26,20 +28,20 @@ pub async fn handler(
 
 async fn process_revoke(
     pool: SqlitePool,
-    alias: Alias,
-    admin_token: String,
-    dir: Dir,
+    aliases: Vec<Alias>,
+    access: AdminAccess,
+    cleaner: &Cleaner,
 ) -> Result<(), Error> {
-    let (id, _size, mut conn) = super::authorize(pool, &alias, &admin_token).await?;
-
-    dir.delete_file(&id)
-        .await
-        .map_err(|_| RevokeError::RemoveFile)?;
+    // Every file must be authorized before removing any of them.
+    let mut ids = Vec::with_capacity(aliases.len());
+    for alias in &aliases {
+        let (id, _size, _conn) = super::authorize(pool.clone(), alias, &access).await?;
+        ids.push(id);
+    }
 
-    sqlx::query(include_query!("delete_file"))
-        .bind(&id)
-        .execute(&mut conn)
-        .await
-        .map_err(|_| RevokeError::PartialRemove)?;
+    let mut conn = pool.acquire().await.map_err(|_| RevokeError::Database)?;
+    for id in ids {
+        cleaner.remove_file(&id, &mut conn).await?;
+    }
     Ok(())
 }
@@ -0,0 +1,56 @@
+use axum::Extension;
+use http_negotiator::{ContentTypeNegotiation, Negotiation};
+use serde::Serialize;
+use sqlx::SqlitePool;
+
+use crate::{
+    alias::Alias,
+    error::{admin as AdminError, Error},
+    include_query,
+    response::{ApiHeader, ApiResponse, ResponseType, SingleLine},
+    update::{new_admin_token, AdminAccess},
+};
+
+#[derive(Serialize)]
+pub struct RotatedToken {
+    admin: String,
+}
+
+impl ApiHeader for RotatedToken {}
+
+impl SingleLine for RotatedToken {
+    fn single_lined(&self) -> String {
+        self.admin.clone()
+    }
+}
+
+// Replaces the admin token of a file, the previous one stops working immediately.
+pub async fn handler(
+    Extension(pool): Extension<SqlitePool>,
+    response_type: Negotiation<ContentTypeNegotiation, ResponseType>,
+    access: AdminAccess,
+    alias: Alias,
+) -> Result<ApiResponse<RotatedToken>, ApiResponse<Error>> {
+    process_rotate(pool, alias, access)
+        .await
+        .map(|token| ApiResponse(*response_type, token))
+        .map_err(|err| ApiResponse(*response_type, err))
+}
+
+async fn process_rotate(
+    pool: SqlitePool,
+    alias: Alias,
+    access: AdminAccess,
+) -> Result<RotatedToken, Error> {
+    let (id, _size, mut conn) = super::authorize(pool, &alias, &access).await?;
+
+    let (admin, hash) = new_admin_token();
+    sqlx::query(include_query!("update_file_admin"))
+        .bind(hash)
+        .bind(&id)
+        .execute(&mut conn)
+        .await
+        .map_err(|_| AdminError::Database)?;
+
+    Ok(RotatedToken { admin })
+}
@@ -0,0 +1,53 @@
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+use crate::error::{admin as AdminError, Error};
+
+// Admin tokens are random UUIDs, so a plain digest is enough to make a leaked database useless.
+pub fn hash_admin_token(admin_token: &str) -> String {
+    hex::encode(Sha256::digest(
+        admin_token.trim().to_ascii_lowercase().as_bytes(),
+    ))
+}
+
+// Returns the token given to the uploader and its hash to store.
+pub fn new_admin_token() -> (String, String) {
+    let admin_token = Uuid::new_v4().as_hyphenated().to_string();
+    let hash = hash_admin_token(&admin_token);
+    (admin_token, hash)
+}
+
+pub fn check_admin_token(admin: &str, admin_token: &str) -> Result<(), Error> {
+    if !constant_time_eq(admin.as_bytes(), hash_admin_token(admin_token).as_bytes()) {
+        return Err(AdminError::InvalidAdminToken);
+    }
+    Ok(())
+}
+
+fn constant_time_eq(lhs: &[u8], rhs: &[u8]) -> bool {
+    lhs.len() == rhs.len() && lhs.iter().zip(rhs).fold(0, |acc, (l, r)| acc | (l ^ r)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{check_admin_token, constant_time_eq, new_admin_token};
+
+    #[test]
+    fn admin_token() {
+        let (token, hash) = new_admin_token();
+        assert_eq!(hash.len(), 64);
+        assert_ne!(token, hash);
+        assert!(check_admin_token(&hash, &token).is_ok());
+        assert!(check_admin_token(&hash, &token.to_ascii_uppercase()).is_ok());
+        assert!(check_admin_token(&hash, &new_admin_token().0).is_err());
+        // Databases which weren't migrated still contain plaintext tokens.
+        assert!(check_admin_token(&token, &token).is_err());
+    }
+
+    #[test]
+    fn comparison() {
+        assert!(constant_time_eq(b"abcd", b"abcd"));
+        assert!(!constant_time_eq(b"abcd", b"abce"));
+        assert!(!constant_time_eq(b"abcd", b"abc"));
+    }
+}
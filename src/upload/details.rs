@@ -0,0 +1,123 @@
+use async_trait::async_trait;
+use axum::extract::{FromRequest, RequestParts};
+use hyper::Body;
+use itertools::Itertools;
+use percent_encoding::percent_decode_str;
+
+use crate::error::Error as UploadError;
+
+const DESCRIPTION_MAX_LENGTH: usize = 1024;
+const TAG_MAX_LENGTH: usize = 32;
+const TAGS_MAX_COUNT: usize = 16;
+
+// Free-text description (X-Description) and comma separated tags (X-Tags), both percent-encoded.
+// Missing headers leave the current values untouched, empty ones clear them.
+pub struct Details {
+    pub description: Option<String>,
+    pub tags: Option<Vec<String>>,
+}
+
+impl Details {
+    pub fn is_empty(&self) -> bool {
+        self.description.is_none() && self.tags.is_none()
+    }
+
+    // Database representations, without empty values.
+    pub fn description_column(&self) -> Option<&str> {
+        self.description.as_deref().filter(|d| !d.is_empty())
+    }
+
+    pub fn tags_column(&self) -> Option<String> {
+        self.tags
+            .as_ref()
+            .filter(|tags| !tags.is_empty())
+            .map(|tags| tags.join(","))
+    }
+}
+
+#[async_trait]
+impl FromRequest<Body> for Details {
+    type Rejection = UploadError;
+
+    async fn from_request(req: &mut RequestParts<Body>) -> Result<Self, Self::Rejection> {
+        let decoded = |name: &str| match req.headers().get(name) {
+            Some(header) => header
+                .to_str()
+                .ok()
+                .and_then(|header| percent_decode_str(header).decode_utf8().ok())
+                .map(|header| Some(header.into_owned()))
+                .ok_or(UploadError::DetailsHeader),
+            None => Ok(None),
+        };
+        Ok(Self {
+            description: decoded("X-Description")?
+                .map(|description| parse_description(&description))
+                .transpose()?,
+            tags: decoded("X-Tags")?
+                .map(|tags| parse_tags(&tags))
+                .transpose()?,
+        })
+    }
+}
+
+fn parse_description(description: &str) -> Result<String, UploadError> {
+    let description = description
+        .chars()
+        .filter(|c| !c.is_control() || *c == '\n')
+        .collect::<String>()
+        .trim()
+        .to_owned();
+    if description.chars().count() > DESCRIPTION_MAX_LENGTH {
+        return Err(UploadError::DetailsHeader);
+    }
+    Ok(description)
+}
+
+fn parse_tags(tags: &str) -> Result<Vec<String>, UploadError> {
+    let tags = tags
+        .split(',')
+        .map(|tag| {
+            tag.chars()
+                .filter(|c| !c.is_control())
+                .collect::<String>()
+                .trim()
+                .to_lowercase()
+        })
+        .filter(|tag| !tag.is_empty())
+        .unique()
+        .collect::<Vec<_>>();
+    if tags.len() > TAGS_MAX_COUNT || tags.iter().any(|tag| tag.chars().count() > TAG_MAX_LENGTH) {
+        return Err(UploadError::DetailsHeader);
+    }
+    Ok(tags)
+}
+
+// Stored tags are joined with commas, which they cannot contain.
+pub fn split_tags(tags: Option<&str>) -> Vec<String> {
+    tags.map(|tags| tags.split(',').map(str::to_owned).collect())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_description, parse_tags};
+
+    #[test]
+    fn description() {
+        assert_eq!(
+            parse_description("  Fixed build\r\nfor ARM\t ").unwrap(),
+            "Fixed build\nfor ARM"
+        );
+        assert!(parse_description(&"a".repeat(1025)).is_err());
+    }
+
+    #[test]
+    fn tags() {
+        assert_eq!(
+            parse_tags(" Release, linux,,release ").unwrap(),
+            ["release", "linux"]
+        );
+        assert!(parse_tags("").unwrap().is_empty());
+        assert!(parse_tags(&"a".repeat(33)).is_err());
+    }
+}
@@ -11,22 +11,30 @@ use serde::Serialize;
 use crate::{
     error::{upload as UploadError, Error},
     misc::format_duration,
+    qr::Qr,
     response::{ApiHeader, SingleLine},
 };
 
 #[derive(Serialize)]
 pub struct UploadInfo {
-    admin: String,
+    // Only known when the token has just been generated or was given by the caller.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    admin: Option<String>,
     name: String,
     size: Size,
     alias: Aliases,
     link: Links,
     expiration: ExpirationGroup,
+    // Collection the file was grouped into, for uploads of a group.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    collection: Option<Links>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    qr: Option<String>,
 }
 
 impl UploadInfo {
     pub fn new(
-        admin: String,
+        admin: Option<String>,
         name: String,
         size: u64,
         alias: (String, String),
@@ -37,22 +45,28 @@ impl UploadInfo {
             admin,
             name,
             size: Size::from(size),
-            alias: Aliases {
-                short: alias.0.clone(),
-                long: alias.1.clone(),
-            },
-            link: Links {
-                short: format!("{}/{}", link_base, &alias.0),
-                long: format!("{}/{}", link_base, &alias.1),
-            },
+            alias: Aliases::new(&alias),
+            link: Links::new(&link_base, &alias),
             expiration: ExpirationGroup {
                 current: expiration.0.clone(),
                 allowed: expiration
                     .1
                     .unwrap_or_else(|| expiration.0.duration.clone()),
             },
+            collection: None,
+            qr: None,
         }
     }
+
+    pub fn with_collection(mut self, link_base: &str, alias: &(String, String)) -> Self {
+        self.collection = Some(Links::new(link_base, alias));
+        self
+    }
+
+    pub fn with_qr(mut self) -> Self {
+        self.qr = Qr::new(&self.link.short).map(|qr| qr.text());
+        self
+    }
 }
 
 impl ApiHeader for UploadInfo {
@@ -63,14 +77,17 @@ impl ApiHeader for UploadInfo {
 
 impl SingleLine for UploadInfo {
     fn single_lined(&self) -> String {
-        self.link.short.clone()
+        match &self.qr {
+            Some(qr) => format!("{}\n{}", self.link.short, qr),
+            None => self.link.short.clone(),
+        }
     }
 }
 
 #[derive(Serialize)]
 pub struct Size {
-    bytes: u64,
-    readable: String,
+    pub bytes: u64,
+    pub readable: String,
 }
 
 impl From<u64> for Size {
@@ -86,14 +103,32 @@ impl From<u64> for Size {
 
 #[derive(Serialize)]
 pub struct Aliases {
-    short: String,
-    long: String,
+    pub short: String,
+    pub long: String,
+}
+
+impl Aliases {
+    pub fn new(alias: &(String, String)) -> Self {
+        Self {
+            short: alias.0.clone(),
+            long: alias.1.clone(),
+        }
+    }
 }
 
 #[derive(Serialize)]
 pub struct Links {
-    short: String,
-    long: String,
+    pub short: String,
+    pub long: String,
+}
+
+impl Links {
+    pub fn new(link_base: &str, alias: &(String, String)) -> Self {
+        Self {
+            short: format!("{}/{}", link_base, &alias.0),
+            long: format!("{}/{}", link_base, &alias.1),
+        }
+    }
 }
 
 #[derive(Serialize)]
@@ -148,6 +183,9 @@ impl From<Duration> for ExpirationDuration {
     }
 }
 
+// 9999-12-31T23:59:59Z, the last date RFC 3339 can represent, also storable as an i64 timestamp.
+pub const MAX_TIMESTAMP: u64 = 253_402_300_799;
+
 #[derive(Serialize, Clone)]
 pub struct ExpirationDate {
     pub timestamp: u64,
@@ -158,12 +196,18 @@ impl TryFrom<Duration> for ExpirationDate {
     type Error = Error;
 
     fn try_from(duration: Duration) -> Result<Self, Self::Error> {
-        let expiration = SystemTime::now() + duration;
+        let expiration = SystemTime::now()
+            .checked_add(duration)
+            .ok_or(UploadError::ExpirationTooHigh)?;
+        let timestamp = expiration
+            .duration_since(UNIX_EPOCH)
+            .map_err(|_| UploadError::TimeCalculation)?
+            .as_secs();
+        if timestamp > MAX_TIMESTAMP {
+            return Err(UploadError::ExpirationTooHigh);
+        }
         Ok(Self {
-            timestamp: expiration
-                .duration_since(UNIX_EPOCH)
-                .map_err(|_| UploadError::TimeCalculation)?
-                .as_secs(),
+            timestamp,
             readable: {
                 let mut full = format_rfc3339_seconds(expiration).to_string();
                 full.truncate(full.len() - 4);
@@ -172,3 +216,17 @@ impl TryFrom<Duration> for ExpirationDate {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::{convert::TryFrom, time::Duration};
+
+    use super::ExpirationDate;
+
+    #[test]
+    fn expiration_date() {
+        assert!(ExpirationDate::try_from(Duration::from_secs(3600)).is_ok());
+        assert!(ExpirationDate::try_from(Duration::from_secs(u64::MAX)).is_err());
+        assert!(ExpirationDate::try_from(Duration::from_secs(i64::MAX as u64)).is_err());
+    }
+}
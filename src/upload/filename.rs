@@ -15,14 +15,61 @@ impl FromRequest<Body> for Filename {
     async fn from_request(req: &mut RequestParts<Body>) -> Result<Self, Self::Rejection> {
         if let Some(header) = req.headers().get("X-Filename") {
             let header = header.to_str().map_err(|_| UploadError::FilenameHeader)?;
-            let filename = sanitize(
-                percent_decode_str(header)
+            let filename = normalize(
+                &percent_decode_str(header)
                     .decode_utf8()
                     .map_err(|_| UploadError::FilenameHeader)?,
-            );
-            Ok(Self(Some(filename)))
+            )?;
+            Ok(Self(filename))
         } else {
             Ok(Self(None))
         }
     }
 }
+
+// Relative directories are kept (e.g. dir/sub/file.txt), but each component is sanitized
+// and parent references are refused.
+fn normalize(path: &str) -> Result<Option<String>, UploadError> {
+    let mut components = Vec::new();
+    for component in path.split(['/', '\\']) {
+        match component {
+            "" | "." => continue,
+            ".." => return Err(UploadError::FilenameHeader),
+            _ => {
+                let component = sanitize(component);
+                if !component.is_empty() {
+                    components.push(component);
+                }
+            }
+        }
+    }
+    Ok(if components.is_empty() {
+        None
+    } else {
+        Some(components.join("/"))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::normalize;
+
+    #[test]
+    fn normalize_path() {
+        assert_eq!(normalize("file.txt").unwrap().unwrap(), "file.txt");
+        assert_eq!(
+            normalize("dir/sub/file.txt").unwrap().unwrap(),
+            "dir/sub/file.txt"
+        );
+        assert_eq!(
+            normalize("/dir//./sub\\file.txt").unwrap().unwrap(),
+            "dir/sub/file.txt"
+        );
+        assert_eq!(
+            normalize("dir/fi:le?.txt").unwrap().unwrap(),
+            "dir/file.txt"
+        );
+        assert!(normalize("").unwrap().is_none());
+        assert!(normalize("dir/../../etc/passwd").is_err());
+    }
+}
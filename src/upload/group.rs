@@ -0,0 +1,104 @@
+use sqlx::{Connection, SqlitePool};
+use uuid::Uuid;
+
+use crate::{
+    alias,
+    error::{upload as UploadError, Error},
+    include_query,
+    storage::Scheduler,
+    update::hash_admin_token,
+};
+
+// Group tokens become the admin token of their collections, so they must be as hard to guess.
+pub(super) fn check_token(token: &str) -> Result<(), Error> {
+    match Uuid::parse_str(token) {
+        Ok(_) => Ok(()),
+        Err(_) => Err(UploadError::InvalidGroup),
+    }
+}
+
+// Only files within a directory are grouped, under their top-level one (dir/sub/file.txt).
+pub(super) fn root_folder(filename: &str) -> Option<&str> {
+    filename.split_once('/').map(|(root, _)| root)
+}
+
+// Adds the file to the collection of its root folder, creating it on the first file of the group.
+// Returns the collection aliases.
+pub(super) async fn attach(
+    pool: &SqlitePool,
+    scheduler: &Scheduler,
+    token: &str,
+    folder: &str,
+    origin: &str,
+    file_id: &str,
+    expiration: u64,
+) -> Result<(String, String), Error> {
+    let admin_hash = hash_admin_token(token);
+    let mut conn = pool.acquire().await.map_err(|_| UploadError::Database)?;
+    let mut tx = conn.begin().await.map_err(|_| UploadError::Database)?;
+
+    let existing =
+        sqlx::query_as::<_, (String, String, String)>(include_query!("get_collection_group"))
+            .bind(&admin_hash)
+            .bind(origin)
+            .bind(folder)
+            .fetch_optional(&mut tx)
+            .await
+            .map_err(|_| UploadError::Database)?;
+    let (id, short, long) = match existing {
+        Some(collection) => collection,
+        None => {
+            let (short, long) = alias::random_unused_aliases(&mut tx)
+                .await
+                .ok_or(UploadError::AliasGeneration)?;
+            let id = Uuid::new_v4().as_hyphenated().to_string();
+            sqlx::query(include_query!("insert_collection"))
+                .bind(&id)
+                .bind(&admin_hash)
+                .bind(origin)
+                .bind(expiration as i64)
+                .bind(folder)
+                .bind(&short)
+                .bind(&long)
+                .execute(&mut tx)
+                .await
+                .map_err(|_| UploadError::Database)?;
+            (id, short, long)
+        }
+    };
+
+    // The collection lasts at least as long as its latest file.
+    sqlx::query(include_query!("extend_collection_member"))
+        .bind(expiration as i64)
+        .bind(&id)
+        .execute(&mut tx)
+        .await
+        .map_err(|_| UploadError::Database)?;
+    sqlx::query(include_query!("insert_collection_file_last"))
+        .bind(&id)
+        .bind(file_id)
+        .execute(&mut tx)
+        .await
+        .map_err(|_| UploadError::Database)?;
+    tx.commit().await.map_err(|_| UploadError::Database)?;
+    scheduler.schedule(expiration);
+
+    Ok((short, long))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{check_token, root_folder};
+
+    #[test]
+    fn folder() {
+        assert_eq!(root_folder("dir/sub/file.txt"), Some("dir"));
+        assert_eq!(root_folder("file.txt"), None);
+    }
+
+    #[test]
+    fn token() {
+        assert!(check_token("0b6f9d3e-4a47-4a83-9d42-7c1e8b2f5a10").is_ok());
+        assert!(check_token("folder").is_err());
+    }
+}
@@ -0,0 +1,136 @@
+use std::str;
+
+const OCTET_STREAM: &str = "application/octet-stream";
+// Only UTF-8 text can be highlighted as a paste.
+pub const UTF8_TEXT: &str = "text/plain; charset=utf-8";
+
+// Byte order marks, checked before the MPEG frame heuristic as FF FE looks like a frame sync.
+const BOMS: &[(&[u8], &str)] = &[
+    (b"\xef\xbb\xbf", UTF8_TEXT),
+    (b"\xff\xfe", "text/plain; charset=utf-16le"),
+    (b"\xfe\xff", "text/plain; charset=utf-16be"),
+];
+
+// Brands of ISO base media files playable as MP4 videos.
+const MP4_BRANDS: &[&[u8]] = &[b"isom", b"iso2", b"mp41", b"mp42", b"avc1", b"dash"];
+
+// Signatures checked against the beginning of the body, with an optional offset.
+const SIGNATURES: &[(usize, &[u8], &str)] = &[
+    (0, b"\x89PNG\r\n\x1a\n", "image/png"),
+    (0, b"\xff\xd8\xff", "image/jpeg"),
+    (0, b"GIF87a", "image/gif"),
+    (0, b"GIF89a", "image/gif"),
+    (0, b"%PDF-", "application/pdf"),
+    (0, b"\x1a\x45\xdf\xa3", "video/webm"),
+    (0, b"OggS", "audio/ogg"),
+    (0, b"fLaC", "audio/flac"),
+    (0, b"ID3", "audio/mpeg"),
+    (0, b"PK\x03\x04", "application/zip"),
+    (0, b"\x1f\x8b", "application/gzip"),
+    (0, b"\x28\xb5\x2f\xfd", "application/zstd"),
+    (0, b"7z\xbc\xaf\x27\x1c", "application/x-7z-compressed"),
+    (257, b"ustar", "application/x-tar"),
+];
+
+pub fn sniff(sample: &[u8]) -> &'static str {
+    if sample.is_empty() {
+        return OCTET_STREAM;
+    }
+    if let Some((_, _, mime)) = SIGNATURES.iter().find(|(offset, signature, _)| {
+        sample
+            .get(*offset..)
+            .map(|sample| sample.starts_with(signature))
+            .unwrap_or(false)
+    }) {
+        return mime;
+    }
+    if let Some((_, mime)) = BOMS.iter().find(|(bom, _)| sample.starts_with(bom)) {
+        return mime;
+    }
+
+    // RIFF and ISO base media containers have their type after the size field.
+    match (sample.get(..4), sample.get(4..8), sample.get(8..12)) {
+        (Some(b"RIFF"), _, Some(b"WEBP")) => return "image/webp",
+        (Some(b"RIFF"), _, Some(b"WAVE")) => return "audio/wav",
+        (_, Some(b"ftyp"), Some(brand)) => {
+            return match brand {
+                b"avif" => "image/avif",
+                b"qt  " => "video/quicktime",
+                b"M4A " => "audio/mp4",
+                brand if MP4_BRANDS.contains(&brand) => "video/mp4",
+                // HEIF images and other ISO base media files.
+                _ => OCTET_STREAM,
+            };
+        }
+        _ => (),
+    }
+    if is_mpeg_frame(sample) {
+        return "audio/mpeg";
+    }
+
+    if is_text(sample) {
+        UTF8_TEXT
+    } else {
+        OCTET_STREAM
+    }
+}
+
+// MPEG audio frame header without tags: frame sync, then valid version, layer, bitrate
+// and sampling rate fields.
+fn is_mpeg_frame(sample: &[u8]) -> bool {
+    match sample.get(..3) {
+        Some(&[0xff, b1, b2]) => {
+            b1 & 0xe0 == 0xe0
+                && (b1 >> 3) & 0b11 != 0b01
+                && (b1 >> 1) & 0b11 != 0b00
+                && !matches!(b2 >> 4, 0b0000 | 0b1111)
+                && (b2 >> 2) & 0b11 != 0b11
+        }
+        _ => false,
+    }
+}
+
+// The sample may end in the middle of a character.
+fn is_text(sample: &[u8]) -> bool {
+    if sample.contains(&0) {
+        return false;
+    }
+    match str::from_utf8(sample) {
+        Ok(_) => true,
+        Err(err) => err.error_len().is_none(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::sniff;
+
+    #[test]
+    fn signatures() {
+        assert_eq!(sniff(b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR"), "image/png");
+        assert_eq!(sniff(b"RIFF\0\0\0\0WEBPVP8 "), "image/webp");
+        assert_eq!(sniff(b"\0\0\0\x20ftypisom\0\0\x02\0"), "video/mp4");
+        assert_eq!(
+            sniff(b"\0\0\0\x18ftypheic\0\0\0\0"),
+            "application/octet-stream"
+        );
+        assert_eq!(sniff(b"%PDF-1.7\n"), "application/pdf");
+        assert_eq!(sniff(b"\xff\xfb\x90\x64"), "audio/mpeg");
+        assert_eq!(sniff(b"\xff\xfb\xf0\x64"), "application/octet-stream");
+        let mut tar = vec![0; 512];
+        tar[257..262].copy_from_slice(b"ustar");
+        assert_eq!(sniff(&tar), "application/x-tar");
+    }
+
+    #[test]
+    fn text() {
+        assert_eq!(sniff(b"hello world\n"), "text/plain; charset=utf-8");
+        assert_eq!(
+            sniff(&"truncated \u{e9}".as_bytes()[..11]),
+            "text/plain; charset=utf-8"
+        );
+        assert_eq!(sniff(b"\xff\xfeh\0i\0"), "text/plain; charset=utf-16le");
+        assert_eq!(sniff(b"\0\x01\x02"), "application/octet-stream");
+        assert_eq!(sniff(b""), "application/octet-stream");
+    }
+}
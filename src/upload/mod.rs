@@ -1,17 +1,26 @@
-use std::{convert::TryFrom, net::SocketAddr, sync::Arc};
+use std::{
+    convert::TryFrom,
+    sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
+};
 
+use async_compression::tokio::write::ZstdEncoder;
 use axum::{
-    extract::{BodyStream, ConnectInfo},
+    extract::{BodyStream, Query},
     headers::{authorization::Basic, Authorization, ContentLength, Cookie},
-    routing::post,
+    routing::{post, put},
     Extension, Router, TypedHeader,
 };
-use file::UploadInfo;
-use filename::Filename;
 use futures::StreamExt;
 use http_negotiator::{ContentTypeNegotiation, Negotiation};
+use modified::LastModified;
+use paste::PasteRequest;
+use serde::Deserialize;
 use sqlx::SqlitePool;
-use tokio::{fs::File, io::AsyncWriteExt};
+use tokio::{
+    fs::File,
+    io::{AsyncWrite, AsyncWriteExt},
+};
 use uuid::Uuid;
 
 use crate::{
@@ -21,23 +30,49 @@ use crate::{
     include_query,
     limit::{Chain as ChainLimiter, Limiter},
     response::{ApiResponse, ResponseType},
-    storage::Dir,
-    upload::{file::ExpirationDuration, origin::ForwardedForHeader},
+    storage::{Compression, Dir, Scheduler},
+    update::new_admin_token,
+    upload::origin::RemoteIp,
 };
 
+mod details;
 mod expiration;
 mod file;
 mod filename;
+mod group;
+mod mime;
+mod modified;
 mod origin;
+mod paste;
+mod replace;
 
+pub use details::{split_tags, Details};
 pub use expiration::{Determiner, Threshold};
-pub use file::Expiration;
+pub use file::{Aliases, Expiration, ExpirationDuration, Links, Size, UploadInfo, MAX_TIMESTAMP};
+pub use filename::Filename;
 pub use origin::{DomainUri, RealIp};
 
+// Size of the body beginning used to detect its content type and estimate its compressibility.
+const SAMPLE_SIZE: usize = 64 * 1024;
+// Larger text bodies are stored as regular files, as they would be too long to highlight.
+const PASTE_MAX_SIZE: u64 = 1024 * 1024;
+
 pub struct UploadRequest {
     pub filename: Option<String>,
     pub size: u64,
+    pub modified: Option<u64>,
     pub origin: String,
+    // Size of the content being replaced, if any.
+    pub replaced: Option<u64>,
+}
+
+#[derive(Deserialize)]
+pub struct UploadQuery {
+    // Appends a terminal rendering of the short link QR code to the response.
+    #[serde(default)]
+    qr: bool,
+    // Groups folder uploads sharing this token into one collection per root folder.
+    group: Option<String>,
 }
 
 #[allow(clippy::too_many_arguments)]
@@ -47,16 +82,25 @@ pub async fn handler(
     authenticator: Extension<Arc<Authenticator>>,
     auth_header: Option<TypedHeader<Authorization<Basic>>>,
     cookie: Option<TypedHeader<Cookie>>,
-    Extension(real_ip): Extension<RealIp>,
-    ConnectInfo(addr): ConnectInfo<SocketAddr>,
-    forwarded_address: Option<ForwardedForHeader>,
+    RemoteIp(remote_ip): RemoteIp,
     Extension(origin): Extension<Origin>,
     Extension(limiter): Extension<Arc<ChainLimiter>>,
     Extension(determiner): Extension<Arc<Determiner>>,
-    Extension(dir): Extension<Dir>,
+    // Grouped, as axum handlers are limited to 16 extractors.
+    (Extension(dir), Extension(compression), Extension(scheduler)): (
+        Extension<Dir>,
+        Extension<Option<Compression>>,
+        Extension<Arc<Scheduler>>,
+    ),
     DomainUri(domain_uri): DomainUri,
     TypedHeader(ContentLength(size)): TypedHeader<ContentLength>,
-    Filename(filename): Filename,
+    (Filename(filename), LastModified(modified), details, Query(upload_query)): (
+        Filename,
+        LastModified,
+        Details,
+        Query<UploadQuery>,
+    ),
+    paste: Option<PasteRequest>,
     body: BodyStream,
 ) -> Result<ApiResponse<UploadInfo>, ApiResponse<Error>> {
     let username = match authenticator
@@ -76,18 +120,34 @@ pub async fn handler(
     };
 
     let origin = match origin {
-        Origin::IpAddress => real_ip
-            .resolve(addr.ip(), forwarded_address.map(|fa| fa.0))
+        Origin::IpAddress => remote_ip
             .ok_or(ApiResponse(*response_type, UploadError::Origin))?
             .to_string(),
         Origin::Username => username.ok_or(ApiResponse(*response_type, UploadError::Origin))?,
     };
 
     let info = process_upload(
-        pool, limiter, origin, determiner, domain_uri, dir, size, filename, body,
+        pool,
+        limiter,
+        origin,
+        determiner,
+        domain_uri,
+        dir,
+        compression,
+        &scheduler,
+        size,
+        filename,
+        modified,
+        details,
+        upload_query.group.as_deref(),
+        paste,
+        body,
     )
     .await
     .map_err(|err| ApiResponse(*response_type, err))?;
+    if upload_query.qr {
+        return Ok(ApiResponse(*response_type, info.with_qr()));
+    }
     Ok(ApiResponse(*response_type, info))
 }
 
@@ -99,14 +159,25 @@ async fn process_upload(
     determiner: Arc<Determiner>,
     domain_uri: String,
     dir: Dir,
+    compression: Option<Compression>,
+    scheduler: &Scheduler,
     size: u64,
     filename: Option<String>,
+    modified: Option<u64>,
+    details: Details,
+    group: Option<&str>,
+    paste: Option<PasteRequest>,
     body: BodyStream,
 ) -> Result<UploadInfo, Error> {
+    if let Some(token) = group {
+        group::check_token(token)?;
+    }
     let upload_req = UploadRequest {
         filename,
         size,
+        modified,
         origin,
+        replaced: None,
     };
     let mut conn = pool.acquire().await.map_err(|_| UploadError::Database)?;
 
@@ -131,51 +202,167 @@ async fn process_upload(
     let default_expiration = Expiration::try_from(default_duration)?;
 
     let id = Uuid::new_v4().as_hyphenated().to_string();
-    let admin = Uuid::new_v4().as_hyphenated().to_string();
+    let (admin, admin_hash) = new_admin_token();
+    let uploaded = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_err(|_| UploadError::TimeCalculation)?
+        .as_secs();
 
     sqlx::query(include_query!("insert_file"))
         .bind(&id)
-        .bind(&admin)
+        .bind(admin_hash)
         .bind(upload_req.origin.to_string())
         .bind(default_expiration.timestamp() as i64)
         .bind(&upload_req.filename)
         .bind(upload_req.size as i64)
         .bind(&short)
         .bind(&long)
+        .bind(uploaded as i64)
+        .bind(upload_req.modified.map(|modified| modified as i64))
+        .bind(details.description_column())
+        .bind(details.tags_column())
         .execute(&mut conn)
         .await
         .map_err(|_| UploadError::Database)?;
     drop(conn);
 
-    // Copy body to file system.
+    // Copy body to the staging area, then atomically move it to the uploads directory.
     let file = dir
-        .create_file(&id)
+        .create_staging_file(&id)
         .await
         .map_err(|_| UploadError::CreateFile)?;
-    if let Err(err) = write_file(&upload_req, body, file).await {
-        clean_failed_upload(&dir, &id, &pool).await;
-        return Err(err);
+    let (compression, mime) = match write_file(&upload_req, body, file, compression).await {
+        Ok(written) => written,
+        Err(err) => {
+            clean_failed_upload(&dir, &id, &pool, false).await;
+            return Err(err);
+        }
+    };
+    let language = paste
+        .filter(|_| upload_req.size <= PASTE_MAX_SIZE && mime == mime::UTF8_TEXT)
+        .map(|paste| paste.language(upload_req.filename.as_deref()));
+    if dir.commit_file(&id).await.is_err() {
+        clean_failed_upload(&dir, &id, &pool, false).await;
+        return Err(UploadError::CopyFile);
+    }
+
+    // Make the file visible to downloads.
+    if sqlx::query(include_query!("complete_file"))
+        .bind(compression)
+        .bind(mime)
+        .bind(language)
+        .bind(&id)
+        .execute(&pool)
+        .await
+        .is_err()
+    {
+        clean_failed_upload(&dir, &id, &pool, true).await;
+        return Err(UploadError::Database);
     }
+    scheduler.schedule(default_expiration.timestamp());
+
+    let folder = upload_req.filename.as_deref().and_then(group::root_folder);
+    let collection = match (group, folder) {
+        (Some(token), Some(folder)) => {
+            match group::attach(
+                &pool,
+                scheduler,
+                token,
+                folder,
+                &upload_req.origin,
+                &id,
+                default_expiration.timestamp(),
+            )
+            .await
+            {
+                Ok(aliases) => Some(aliases),
+                Err(err) => {
+                    clean_failed_upload(&dir, &id, &pool, true).await;
+                    return Err(err);
+                }
+            }
+        }
+        _ => None,
+    };
 
-    Ok(UploadInfo::new(
-        admin,
+    let info = UploadInfo::new(
+        Some(admin),
         upload_req.filename.unwrap_or_else(|| long.clone()),
         upload_req.size,
         (short, long),
-        domain_uri,
+        domain_uri.clone(),
         (
             default_expiration,
             allowed_duration.map(ExpirationDuration::from),
         ),
-    ))
+    );
+    Ok(match collection {
+        Some(aliases) => info.with_collection(&domain_uri, &aliases),
+        None => info,
+    })
 }
 
 async fn write_file(
     req: &UploadRequest,
     mut body: BodyStream,
-    mut file: File,
-) -> Result<(), Error> {
+    file: File,
+    compression: Option<Compression>,
+) -> Result<(Option<Compression>, &'static str), Error> {
     let mut written = 0;
+    let mut sample = Vec::new();
+    while sample.len() < SAMPLE_SIZE {
+        let data = match body.next().await {
+            Some(chunk) => chunk.map_err(|_| UploadError::CopyFile)?,
+            None => break,
+        };
+        if written + data.len() as u64 > req.size {
+            return Err(UploadError::SizeMismatch);
+        }
+        written += data.len() as u64;
+        sample.extend_from_slice(&data);
+    }
+    let mime = mime::sniff(&sample);
+    let compression = match compression {
+        Some(compression) if compression.is_worth(&sample).await => Some(compression),
+        _ => None,
+    };
+
+    match compression {
+        Some(Compression::Zstd) => {
+            let mut encoder = ZstdEncoder::new(file);
+            copy_body(req, &sample, written, body, &mut encoder).await?;
+            encoder
+                .shutdown()
+                .await
+                .map_err(|_| UploadError::CopyFile)?;
+            encoder
+                .into_inner()
+                .sync_all()
+                .await
+                .map_err(|_| UploadError::CopyFile)?;
+        }
+        None => {
+            let mut file = file;
+            copy_body(req, &sample, written, body, &mut file).await?;
+            file.sync_all().await.map_err(|_| UploadError::CopyFile)?;
+        }
+    }
+    Ok((compression, mime))
+}
+
+async fn copy_body<W>(
+    req: &UploadRequest,
+    sample: &[u8],
+    mut written: u64,
+    mut body: BodyStream,
+    writer: &mut W,
+) -> Result<(), Error>
+where
+    W: AsyncWrite + Unpin,
+{
+    if writer.write_all(sample).await.is_err() {
+        return Err(UploadError::CopyFile);
+    }
     while let Some(chunk) = body.next().await {
         let data = chunk.map_err(|_| UploadError::CopyFile)?;
 
@@ -184,7 +371,7 @@ async fn write_file(
         }
         written += data.len() as u64;
 
-        if file.write_all(&data).await.is_err() {
+        if writer.write_all(&data).await.is_err() {
             return Err(UploadError::CopyFile);
         }
     }
@@ -196,8 +383,13 @@ async fn write_file(
     Ok(())
 }
 
-async fn clean_failed_upload(dir: &Dir, id: &str, pool: &SqlitePool) {
-    if let Err(err) = dir.delete_file(id).await {
+async fn clean_failed_upload(dir: &Dir, id: &str, pool: &SqlitePool, committed: bool) {
+    let deleted = if committed {
+        dir.delete_file(id).await
+    } else {
+        dir.delete_staging_file(id).await
+    };
+    if let Err(err) = deleted {
         log::error!(
             "Cannot remove file with id {} from file system, file will retain quota: {}",
             id,
@@ -206,7 +398,7 @@ async fn clean_failed_upload(dir: &Dir, id: &str, pool: &SqlitePool) {
         return;
     }
     if let Err(err) = sqlx::query(include_query!("delete_file"))
-        .bind(&id)
+        .bind(id)
         .execute(pool)
         .await
     {
@@ -214,6 +406,7 @@ async fn clean_failed_upload(dir: &Dir, id: &str, pool: &SqlitePool) {
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn router(
     pool: SqlitePool,
     auth: Arc<Authenticator>,
@@ -222,10 +415,13 @@ pub fn router(
     limiters: ChainLimiter,
     determiner: Arc<Determiner>,
     dir: Dir,
+    compression: Option<Compression>,
+    scheduler: Arc<Scheduler>,
 ) -> Router {
     Router::new()
         .route("/", post(handler))
         .route("/upload", post(handler))
+        .route("/:alias", put(replace::handler))
         .route_layer(Extension(pool))
         .route_layer(Extension(auth))
         .route_layer(Extension(real_ip))
@@ -233,4 +429,6 @@ pub fn router(
         .route_layer(Extension(Arc::new(limiters)))
         .route_layer(Extension(determiner))
         .route_layer(Extension(dir))
+        .route_layer(Extension(compression))
+        .route_layer(Extension(scheduler))
 }
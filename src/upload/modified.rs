@@ -0,0 +1,51 @@
+use std::time::UNIX_EPOCH;
+
+use async_trait::async_trait;
+use axum::extract::{FromRequest, RequestParts};
+use hyper::Body;
+
+use crate::error::Error as UploadError;
+
+// Modification time of the uploaded file as a UNIX timestamp in seconds.
+pub struct LastModified(pub Option<u64>);
+
+#[async_trait]
+impl FromRequest<Body> for LastModified {
+    type Rejection = UploadError;
+
+    async fn from_request(req: &mut RequestParts<Body>) -> Result<Self, Self::Rejection> {
+        if let Some(header) = req.headers().get("X-Last-Modified") {
+            let header = header
+                .to_str()
+                .map_err(|_| UploadError::LastModifiedHeader)?;
+            Ok(Self(Some(parse(header)?)))
+        } else {
+            Ok(Self(None))
+        }
+    }
+}
+
+// Browsers expose File.lastModified in milliseconds, RFC 3339 dates are also accepted for scripts.
+fn parse(header: &str) -> Result<u64, UploadError> {
+    if let Ok(millis) = header.trim().parse::<u64>() {
+        return Ok(millis / 1000);
+    }
+    humantime::parse_rfc3339_weak(header.trim())
+        .ok()
+        .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+        .map(|duration| duration.as_secs())
+        .ok_or(UploadError::LastModifiedHeader)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse;
+
+    #[test]
+    fn parse_header() {
+        assert_eq!(parse("1666000000123").unwrap(), 1666000000);
+        assert_eq!(parse("2022-10-17T09:46:40Z").unwrap(), 1666000000);
+        assert!(parse("yesterday").is_err());
+        assert!(parse("-1").is_err());
+    }
+}
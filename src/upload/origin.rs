@@ -1,7 +1,7 @@
-use std::net::IpAddr;
+use std::net::{IpAddr, SocketAddr};
 
 use async_trait::async_trait;
-use axum::extract::{FromRequest, RequestParts};
+use axum::extract::{ConnectInfo, FromRequest, RequestParts};
 use hyper::Body;
 
 use crate::error::Error;
@@ -42,6 +42,26 @@ impl FromRequest<Body> for ForwardedForHeader {
     }
 }
 
+// Resolved uploader's IP address, requires the RealIp extension.
+pub struct RemoteIp(pub Option<IpAddr>);
+
+#[async_trait]
+impl FromRequest<Body> for RemoteIp {
+    type Rejection = Error;
+
+    async fn from_request(req: &mut RequestParts<Body>) -> Result<Self, Self::Rejection> {
+        let real_ip = *req.extensions().get::<RealIp>().ok_or(Error::Origin)?;
+        let ConnectInfo(addr) = ConnectInfo::<SocketAddr>::from_request(req)
+            .await
+            .map_err(|_| Error::Origin)?;
+        let forwarded = ForwardedForHeader::from_request(req)
+            .await
+            .ok()
+            .map(|fa| fa.0);
+        Ok(Self(real_ip.resolve(addr.ip(), forwarded)))
+    }
+}
+
 pub struct DomainUri(pub String);
 
 #[async_trait]
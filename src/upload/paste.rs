@@ -0,0 +1,90 @@
+use async_trait::async_trait;
+use axum::extract::{FromRequest, RequestParts};
+use hyper::{header::CONTENT_TYPE, Body};
+
+use crate::error::Error as UploadError;
+
+const LANGUAGE_MAX_LENGTH: usize = 32;
+
+// Text bodies (Content-Type: text/plain) are kept as pastes, with an optional X-Language hint.
+pub struct PasteRequest {
+    pub language: Option<String>,
+}
+
+impl PasteRequest {
+    // Requested language first, then the file extension, plain text otherwise.
+    pub fn language(self, filename: Option<&str>) -> String {
+        self.language
+            .or_else(|| {
+                filename
+                    .and_then(|name| name.rsplit_once('.'))
+                    .and_then(|(_, extension)| sanitize(extension))
+            })
+            .unwrap_or_else(|| "text".to_owned())
+    }
+}
+
+#[async_trait]
+impl FromRequest<Body> for PasteRequest {
+    type Rejection = UploadError;
+
+    async fn from_request(req: &mut RequestParts<Body>) -> Result<Self, Self::Rejection> {
+        let content_type = req
+            .headers()
+            .get(CONTENT_TYPE)
+            .and_then(|header| header.to_str().ok())
+            .ok_or(UploadError::Generic)?;
+        if !content_type
+            .split(';')
+            .next()
+            .unwrap_or_default()
+            .trim()
+            .eq_ignore_ascii_case("text/plain")
+        {
+            return Err(UploadError::Generic);
+        }
+        Ok(Self {
+            language: req
+                .headers()
+                .get("X-Language")
+                .and_then(|header| header.to_str().ok())
+                .and_then(sanitize),
+        })
+    }
+}
+
+// Languages are only used as syntax lookup tokens (e.g. rust, rs, c++).
+fn sanitize(language: &str) -> Option<String> {
+    let language = language.trim().to_lowercase();
+    if language.is_empty()
+        || language.len() > LANGUAGE_MAX_LENGTH
+        || !language
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "+#-_.".contains(c))
+    {
+        return None;
+    }
+    Some(language)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{sanitize, PasteRequest};
+
+    #[test]
+    fn language() {
+        assert_eq!(sanitize(" Rust ").unwrap(), "rust");
+        assert_eq!(sanitize("c++").unwrap(), "c++");
+        assert!(sanitize("<script>").is_none());
+        assert!(sanitize("").is_none());
+
+        let paste = || PasteRequest { language: None };
+        assert_eq!(paste().language(Some("main.rs")), "rs");
+        assert_eq!(paste().language(Some("notes")), "text");
+        assert_eq!(paste().language(None), "text");
+        let paste = PasteRequest {
+            language: Some("python".to_owned()),
+        };
+        assert_eq!(paste.language(Some("main.rs")), "python");
+    }
+}
@@ -0,0 +1,329 @@
+use std::{
+    convert::TryFrom,
+    io::ErrorKind,
+    sync::Arc,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use axum::{
+    extract::{BodyStream, Query},
+    headers::ContentLength,
+    Extension, TypedHeader,
+};
+use http_negotiator::{ContentTypeNegotiation, Negotiation};
+use serde::Deserialize;
+use sqlx::{Connection, FromRow, Sqlite, SqlitePool, Transaction};
+use uuid::Uuid;
+
+use crate::{
+    alias::Alias,
+    error::{upload as UploadError, Error},
+    include_query,
+    limit::{Chain as ChainLimiter, Limiter},
+    response::{ApiResponse, ResponseType},
+    storage::{Compression, Dir, Scheduler},
+    update::AdminAccess,
+    upload::{
+        filename::Filename, mime, modified::LastModified, paste::PasteRequest, write_file,
+        Determiner, DomainUri, Expiration, ExpirationDuration, UploadInfo, UploadRequest,
+        PASTE_MAX_SIZE,
+    },
+};
+
+#[derive(FromRow)]
+struct ReplacedFile {
+    id: String,
+    admin: String,
+    origin: String,
+    expiration: i64,
+    name: Option<String>,
+    size: i64,
+    short_alias: String,
+    long_alias: String,
+    downloads: Option<i64>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct ReplaceQuery {
+    // Restarts the expiration from the default duration of the new size.
+    #[serde(default)]
+    reset_expiration: bool,
+    // New number of allowed downloads (0 for unlimited), the current one is kept otherwise.
+    downloads: Option<u16>,
+}
+
+// Replaces the content of a file, keeping its aliases and admin token.
+#[allow(clippy::too_many_arguments)]
+pub async fn handler(
+    Extension(pool): Extension<SqlitePool>,
+    response_type: Negotiation<ContentTypeNegotiation, ResponseType>,
+    access: AdminAccess,
+    alias: Alias,
+    Extension(limiter): Extension<Arc<ChainLimiter>>,
+    Extension(determiner): Extension<Arc<Determiner>>,
+    Extension(dir): Extension<Dir>,
+    Extension(compression): Extension<Option<Compression>>,
+    Extension(scheduler): Extension<Arc<Scheduler>>,
+    DomainUri(domain_uri): DomainUri,
+    TypedHeader(ContentLength(size)): TypedHeader<ContentLength>,
+    Filename(filename): Filename,
+    LastModified(modified): LastModified,
+    Query(query): Query<ReplaceQuery>,
+    paste: Option<PasteRequest>,
+    body: BodyStream,
+) -> Result<ApiResponse<UploadInfo>, ApiResponse<Error>> {
+    process_replace(
+        pool,
+        access,
+        alias,
+        limiter,
+        determiner,
+        domain_uri,
+        dir,
+        compression,
+        &scheduler,
+        size,
+        filename,
+        modified,
+        query,
+        paste,
+        body,
+    )
+    .await
+    .map(|info| ApiResponse(*response_type, info))
+    .map_err(|err| ApiResponse(*response_type, err))
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn process_replace(
+    pool: SqlitePool,
+    access: AdminAccess,
+    alias: Alias,
+    limiter: Arc<ChainLimiter>,
+    determiner: Arc<Determiner>,
+    domain_uri: String,
+    dir: Dir,
+    compression: Option<Compression>,
+    scheduler: &Scheduler,
+    size: u64,
+    filename: Option<String>,
+    modified: Option<u64>,
+    query: ReplaceQuery,
+    paste: Option<PasteRequest>,
+    body: BodyStream,
+) -> Result<UploadInfo, Error> {
+    let mut conn = pool.acquire().await.map_err(|_| UploadError::Database)?;
+    let file = sqlx::query_as::<_, ReplacedFile>(include_query!("get_file_replace"))
+        .bind(alias.inner())
+        .bind(alias.inner())
+        .fetch_optional(&mut conn)
+        .await
+        .map_err(|_| UploadError::Database)?
+        .ok_or(UploadError::FileNotFound)?;
+    access.check(&file.admin, &file.origin)?;
+    let admin = match access {
+        AdminAccess::Token(admin) => Some(admin),
+        AdminAccess::Owner(_) | AdminAccess::Administrator(_) => None,
+    };
+
+    // Quota, counted against the uploader of the file.
+    let upload_req = UploadRequest {
+        filename: filename.or(file.name),
+        size,
+        modified,
+        origin: file.origin,
+        replaced: Some(file.size as u64),
+    };
+    if !limiter
+        .accept(&upload_req, &mut conn)
+        .await
+        .ok_or(UploadError::QuotaAccess)?
+    {
+        return Err(UploadError::QuotaExceeded);
+    }
+    drop(conn);
+
+    // Expiration.
+    let (default_duration, allowed_duration) = determiner
+        .determine(upload_req.size)
+        .ok_or(UploadError::TooLarge)?;
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_err(|_| UploadError::TimeCalculation)?
+        .as_secs();
+    let expiration = Expiration::try_from(expiration_duration(
+        Duration::from_secs((file.expiration.max(0) as u64).saturating_sub(now)),
+        (default_duration, allowed_duration),
+        query.reset_expiration,
+    ))?;
+    let downloads = match query.downloads {
+        Some(0) => None,
+        Some(count) => Some(count as i64),
+        None => file.downloads,
+    };
+
+    // The new content gets its own id, so the previous one stays untouched until the swap is
+    // committed, and concurrent replacements don't collide.
+    let id = Uuid::new_v4().as_hyphenated().to_string();
+    let staging = dir
+        .create_staging_file(&id)
+        .await
+        .map_err(|_| UploadError::CreateFile)?;
+    let (compression, mime) = match write_file(&upload_req, body, staging, compression).await {
+        Ok(written) => written,
+        Err(err) => {
+            clean_failed_replace(&dir, &id).await;
+            return Err(err);
+        }
+    };
+    let language = paste
+        .filter(|_| upload_req.size <= PASTE_MAX_SIZE && mime == mime::UTF8_TEXT)
+        .map(|paste| paste.language(upload_req.filename.as_deref()));
+
+    // Without a Last-Modified header, downloads fall back to the replacement time.
+    let mut conn = pool.acquire().await.map_err(|_| UploadError::Database)?;
+    let mut tx = conn.begin().await.map_err(|_| UploadError::Database)?;
+    if !switch_content(
+        &mut tx,
+        &file.id,
+        &id,
+        &upload_req,
+        now,
+        compression,
+        mime,
+        language,
+        expiration.timestamp(),
+        downloads,
+    )
+    .await
+    {
+        clean_failed_replace(&dir, &id).await;
+        return Err(UploadError::Database);
+    }
+    if let Err(err) = dir.commit_file(&id).await {
+        log::error!("Cannot store new content of file {}: {}", file.id, err);
+        clean_failed_replace(&dir, &id).await;
+        return Err(UploadError::CopyFile);
+    }
+    if let Err(err) = tx.commit().await {
+        log::error!("Cannot commit new content of file {}: {:?}", file.id, err);
+        if let Err(err) = dir.delete_file(&id).await {
+            log::error!(
+                "Cannot remove file with id {} from file system: {}",
+                id,
+                err
+            );
+        }
+        return Err(UploadError::Database);
+    }
+    // Downloads that already opened the previous content keep reading it.
+    if let Err(err) = dir.delete_file(&file.id).await {
+        log::error!(
+            "Cannot remove previous content of file {}: {}",
+            file.id,
+            err
+        );
+    }
+    scheduler.schedule(expiration.timestamp());
+
+    let aliases = (file.short_alias, file.long_alias);
+    Ok(UploadInfo::new(
+        admin,
+        upload_req.filename.unwrap_or_else(|| aliases.1.clone()),
+        upload_req.size,
+        aliases,
+        domain_uri,
+        (expiration, allowed_duration.map(ExpirationDuration::from)),
+    ))
+}
+
+// The remaining duration is kept, unless longer than what the new size allows.
+fn expiration_duration(
+    remaining: Duration,
+    (default, allowed): (Duration, Option<Duration>),
+    reset: bool,
+) -> Duration {
+    if reset {
+        default
+    } else {
+        remaining.min(allowed.unwrap_or(default))
+    }
+}
+
+// Moves the row, and the collections it belongs to, over to the new content.
+#[allow(clippy::too_many_arguments)]
+async fn switch_content(
+    tx: &mut Transaction<'_, Sqlite>,
+    previous_id: &str,
+    id: &str,
+    upload_req: &UploadRequest,
+    now: u64,
+    compression: Option<Compression>,
+    mime: &str,
+    language: Option<String>,
+    expiration: u64,
+    downloads: Option<i64>,
+) -> bool {
+    // Memberships are updated after the file itself, so their constraint is checked on commit.
+    if sqlx::query("PRAGMA defer_foreign_keys = ON")
+        .execute(&mut *tx)
+        .await
+        .is_err()
+    {
+        return false;
+    }
+    let replaced = sqlx::query(include_query!("replace_file"))
+        .bind(id)
+        .bind(upload_req.size as i64)
+        .bind(&upload_req.filename)
+        .bind(upload_req.modified.map(|modified| modified as i64))
+        .bind(now as i64)
+        .bind(compression)
+        .bind(mime)
+        .bind(language)
+        .bind(expiration as i64)
+        .bind(downloads)
+        .bind(previous_id)
+        .execute(&mut *tx)
+        .await
+        .map(|res| res.rows_affected() == 1)
+        .unwrap_or(false);
+    replaced
+        && sqlx::query(include_query!("update_collection_file_id"))
+            .bind(id)
+            .bind(previous_id)
+            .execute(&mut *tx)
+            .await
+            .is_ok()
+}
+
+async fn clean_failed_replace(dir: &Dir, id: &str) {
+    match dir.delete_staging_file(id).await {
+        Err(err) if err.kind() != ErrorKind::NotFound => log::error!(
+            "Cannot remove staging file {} from file system: {}",
+            id,
+            err
+        ),
+        _ => (),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::expiration_duration;
+
+    #[test]
+    fn expiration() {
+        let hours = |h: u64| Duration::from_secs(h * 3600);
+        let thresholds = (hours(24), Some(hours(48)));
+        assert_eq!(expiration_duration(hours(12), thresholds, false), hours(12));
+        assert_eq!(expiration_duration(hours(12), thresholds, true), hours(24));
+        assert_eq!(
+            expiration_duration(hours(72), (hours(24), None), false),
+            hours(24)
+        );
+    }
+}
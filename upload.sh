@@ -5,6 +5,7 @@ set -e -u -o pipefail
 readonly DOMAIN=""   # Dont forget the protocol (HTTP(S)).
 readonly USERNAME="" # Leave empty if disabled serverside.
 readonly PASSWORD="" # Leave empty if disabled serverside.
+readonly QR_CODE=""  # Set to any value to print links as QR codes.
 
 if [ -z "$DOMAIN" ]; then
   echo "Unspecified domain" >&2
@@ -33,14 +34,20 @@ upload() {
   declare FILE="$1"
   shift
 
+  # Text piped from stdin is kept as a paste.
   if [ "$FILE" == "-" ]; then
-    curl "$@" --data-binary @-; echo
+    curl "$@" --header 'Content-Type: text/plain' --data-binary @-; echo
   else
     declare FILENAME="$(tr -dc '\40'-'\176' <<< $(basename $FILE))"
-    curl "$@" --request POST --header "X-Filename: $FILENAME" --upload-file "$FILE"; echo
+    curl "$@" --request POST --header 'Content-Type:' --header "X-Filename: $FILENAME" --upload-file "$FILE"; echo
   fi
 }
 
+declare QUERY=""
+if [ -n "$QR_CODE" ]; then
+  QUERY="?qr=true"
+fi
+
 for FILE in $FILES; do
-  upload $FILE $CREDENTIALS --header 'Accept: text/plain' --header 'Content-Type:' "$DOMAIN/upload"
+  upload $FILE $CREDENTIALS --header 'Accept: text/plain' "$DOMAIN/upload$QUERY"
 done